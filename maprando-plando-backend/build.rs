@@ -0,0 +1,14 @@
+use std::process::Command;
+
+/// Records the MapRandomizer commit the backend is built against, which seed files store to tell which game data they were made with
+fn main() {
+    let commit = Command::new("git")
+        .args(["-C", "../MapRandomizer", "rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=MAPRANDO_COMMIT={commit}");
+    println!("cargo:rerun-if-changed=../.git/modules/MapRandomizer/HEAD");
+}
@@ -0,0 +1,697 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use anyhow::{bail, Result};
+use hashbrown::{HashMap, HashSet};
+use maprando::{randomize::{DifficultyConfig, Randomization}, settings::{Objective, RandomizerSettings}, spoiler_log::{SpoilerLog, SpoilerRouteEntry}, traverse::LockedDoorData};
+use maprando_game::{GameData, Item, Map, NotableId, Requirement, RoomId, StartLocation, TechId};
+use maprando_logic::{GlobalState, LocalState};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::{logic::{CancelToken, HubLocationData, Logic, SpoilerProgress}, map_editor::MapErrorType, OverrideTarget, Placeable, SpoilerFlagOverride, SpoilerOverride};
+
+/// Returns "Room: Node" of an item location
+pub fn get_item_location_name(game_data: &GameData, item_loc: usize) -> String {
+    let (room_id, node_id) = game_data.item_locations[item_loc];
+    let room_idx = game_data.room_idx_by_id[&room_id];
+    let room_name = &game_data.room_geometry[room_idx].name;
+    let node_name = game_data.node_json_map[&(room_id, node_id)]["name"].as_str().unwrap();
+    format!("{room_name}: {node_name}")
+}
+
+/// Item location idxs which are collected in any step of the spoiler log
+pub fn get_collected_item_locations(game_data: &GameData, spoiler_log: &SpoilerLog) -> HashSet<usize> {
+    let item_loc_map: HashMap<(usize, usize), usize> = game_data.item_locations.iter().enumerate().map(|(idx, &loc)| (loc, idx)).collect();
+    spoiler_log.details.iter().flat_map(|details| {
+        details.items.iter().filter_map(|item| item_loc_map.get(&(item.location.room_id, item.location.node_id)).copied())
+    }).collect()
+}
+
+/// Checks the spoiler overrides against the spoiler log they were applied to: Overrides past the last step in which logic still collects something,
+/// overrides on items and flags which logic already collects in an earlier step, duplicate overrides and overrides without a description
+pub(crate) fn get_override_warnings(
+    game_data: &GameData,
+    spoiler_overrides: &[SpoilerOverride],
+    spoiler_flag_overrides: &[SpoilerFlagOverride],
+    spoiler_log: &SpoilerLog
+) -> Vec<MapErrorType> {
+    let overrides: Vec<(OverrideTarget, usize, &str)> = spoiler_overrides.iter().map(|x| (OverrideTarget::Item(x.item_idx), x.step, x.description.as_str()))
+        .chain(spoiler_flag_overrides.iter().map(|x| (OverrideTarget::Flag(x.flag_id), x.step, x.description.as_str())))
+        .collect();
+    if overrides.is_empty() {
+        return Vec::new();
+    }
+    let overridden: HashSet<(OverrideTarget, usize)> = overrides.iter().map(|&(target, step, _)| (target, step)).collect();
+
+    // First step each item location and flag is collected in, and the last step in which something is collected without an override
+    let item_loc_map: HashMap<(usize, usize), usize> = game_data.item_locations.iter().enumerate().map(|(idx, &loc)| (loc, idx)).collect();
+    let mut collected_step: HashMap<OverrideTarget, usize> = HashMap::new();
+    let mut last_step = 0;
+    for details in &spoiler_log.details {
+        let items = details.items.iter().filter_map(|item| item_loc_map.get(&(item.location.room_id, item.location.node_id)).map(|&idx| OverrideTarget::Item(idx)));
+        let flags = details.flags.iter().filter_map(|flag| game_data.flag_isv.index_by_key.get(&flag.flag).map(|&flag_id| OverrideTarget::Flag(flag_id)));
+        for target in items.chain(flags) {
+            collected_step.entry(target).or_insert(details.step);
+            if !overridden.contains(&(target, details.step)) {
+                last_step = last_step.max(details.step);
+            }
+        }
+    }
+
+    let mut res = Vec::new();
+    let mut seen = HashSet::new();
+    for (target, step, description) in overrides {
+        if !seen.insert(target) {
+            res.push(MapErrorType::OverrideDuplicate(target, step));
+        }
+        if step > last_step + 1 {
+            res.push(MapErrorType::OverridePastLastStep(target, step, last_step));
+        }
+        if let Some(&logic_step) = collected_step.get(&target) {
+            if logic_step < step {
+                res.push(MapErrorType::OverrideInLogic(target, step, logic_step));
+            }
+        }
+        if description.trim().is_empty() {
+            res.push(MapErrorType::OverrideNoDescription(target, step));
+        }
+    }
+    res
+}
+
+pub fn is_mother_brain_defeated(game_data: &GameData, spoiler_log: &SpoilerLog) -> bool {
+    get_mother_brain_step(game_data, spoiler_log).is_some()
+}
+
+/// The escape timer maxes out if the escape is not logically clearable
+pub fn is_escape_logical(randomization: &Randomization) -> bool {
+    randomization.escape_time_seconds != 5995.0 && randomization.escape_time_seconds != 0.0
+}
+
+/// Step in which the Mother Brain defeated flag is collected
+pub fn get_mother_brain_step(game_data: &GameData, spoiler_log: &SpoilerLog) -> Option<usize> {
+    let mb_flag_str = &game_data.flag_isv.keys[game_data.mother_brain_defeated_flag_id];
+    spoiler_log.details.iter().find(|details| {
+        details.flags.iter().any(|flag| flag.flag == *mb_flag_str)
+    }).map(|details| details.step)
+}
+
+#[derive(Clone)]
+pub(crate) struct DifficultyTierInput {
+    pub(crate) name: String,
+    pub(crate) settings: RandomizerSettings,
+    pub(crate) difficulty: DifficultyConfig,
+    pub(crate) difficulty_tiers: Vec<DifficultyConfig>,
+    pub(crate) locked_door_data: LockedDoorData,
+    pub(crate) initial_global_state: GlobalState,
+    pub(crate) initial_local_state: LocalState,
+}
+
+pub struct DifficultyTierResult {
+    pub name: String,
+    pub beatable: bool,
+    /// (Item location idx, item) of items which are in logic on the lowest beatable tier but not on this one
+    pub missing_items: Vec<(usize, Item)>,
+}
+
+pub struct DifficultyAnalysis {
+    /// Results in ascending difficulty, up to the lowest tier the seed is beatable on
+    pub tiers: Vec<DifficultyTierResult>,
+    pub min_tier: Option<usize>,
+}
+
+impl DifficultyAnalysis {
+    pub fn min_tier_name(&self) -> Option<&str> {
+        self.min_tier.map(|idx| self.tiers[idx].name.as_str())
+    }
+
+    pub fn to_string(&self, game_data: &GameData) -> String {
+        let mut lines = vec![match self.min_tier_name() {
+            Some(name) => format!("Lowest beatable skill preset: {name}"),
+            None => "Mother Brain is not logically reachable on any skill preset".to_string()
+        }];
+        for tier in &self.tiers {
+            if tier.beatable {
+                continue;
+            }
+            lines.push(format!("{}: {} items out of logic", tier.name, tier.missing_items.len()));
+            for &(item_loc, item) in &tier.missing_items {
+                let item_name = Placeable::from_item(item).map(|x| x.to_string()).unwrap_or_default();
+                lines.push(format!("    {item_name} ({})", get_item_location_name(game_data, item_loc)));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+pub(crate) async fn analyze_min_difficulty(
+    game_data: Arc<GameData>,
+    tiers: Vec<DifficultyTierInput>,
+    start_location: StartLocation,
+    objectives: Vec<Objective>,
+    item_locations: Vec<Item>,
+    spoiler_overrides: Vec<SpoilerOverride>,
+    spoiler_flag_overrides: Vec<SpoilerFlagOverride>,
+    map: Map,
+    custom_escape_time: Option<usize>,
+    rng_seed: u64
+) -> Result<DifficultyAnalysis> {
+    let mut results = Vec::new();
+    let mut collected_per_tier = Vec::new();
+    let mut min_tier = None;
+
+    for (idx, tier) in tiers.into_iter().enumerate() {
+        let logic = Logic::new(game_data.clone());
+        let handle = logic.update_hub_and_randomization(
+            tier.initial_global_state,
+            tier.initial_local_state,
+            start_location.clone(),
+            tier.locked_door_data,
+            objectives.clone(),
+            tier.difficulty,
+            item_locations.clone(),
+            spoiler_overrides.clone(),
+            spoiler_flag_overrides.clone(),
+            tier.settings,
+            tier.difficulty_tiers,
+            map.clone(),
+            custom_escape_time,
+            rng_seed,
+            false,
+            CancelToken::new(),
+            watch::channel(SpoilerProgress::default()).0
+        );
+        // A tier on which the logic fails entirely (e.g. the start location requires tech) collects nothing
+        let (collected, beatable) = match handle.await? {
+            Ok(()) => match logic.get_randomization().as_ref() {
+                Some((_, s)) => (get_collected_item_locations(&game_data, s), is_mother_brain_defeated(&game_data, s)),
+                None => (HashSet::new(), false)
+            },
+            Err(_) => (HashSet::new(), false)
+        };
+
+        results.push(DifficultyTierResult {
+            name: tier.name,
+            beatable,
+            missing_items: Vec::new()
+        });
+        collected_per_tier.push(collected);
+
+        if beatable {
+            min_tier = Some(idx);
+            break;
+        }
+    }
+
+    // Compare against the lowest beatable tier, or the hardest tier if the seed is not beatable at all
+    if let Some(reference) = collected_per_tier.last().cloned() {
+        for (result, collected) in results.iter_mut().zip(&collected_per_tier) {
+            let mut missing: Vec<usize> = reference.difference(collected).copied().collect();
+            missing.sort();
+            result.missing_items = missing.into_iter().map(|idx| (idx, item_locations[idx])).collect();
+        }
+    }
+
+    Ok(DifficultyAnalysis {
+        tiers: results,
+        min_tier
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ItemRequirement {
+    Required, // Mother Brain or the escape is not logically reachable without the item
+    SoftRequired, // The seed is still beatable without the item, but Mother Brain is defeated in a later step
+    Optional
+}
+
+impl ItemRequirement {
+    pub fn to_string(&self) -> String {
+        match self {
+            ItemRequirement::Required => "Required",
+            ItemRequirement::SoftRequired => "Soft-required",
+            ItemRequirement::Optional => "Optional"
+        }.to_string()
+    }
+}
+
+pub struct RequiredItemResult {
+    pub item_loc: usize,
+    pub item: Item,
+    pub requirement: ItemRequirement,
+    pub mother_brain_step: Option<usize>, // Step Mother Brain is defeated in without the item
+}
+
+/// Requirement of every placed progression item which is collected by the logic, ordered by item location
+pub struct RequiredItemsAnalysis {
+    pub mother_brain_step: usize, // Step Mother Brain is defeated in with all items placed
+    pub items: Vec<RequiredItemResult>,
+}
+
+impl RequiredItemsAnalysis {
+    pub fn get_item(&self, item_loc: usize) -> Option<&RequiredItemResult> {
+        self.items.iter().find(|x| x.item_loc == item_loc)
+    }
+
+    pub fn to_string(&self, game_data: &GameData) -> String {
+        let mut lines = vec![format!("Mother Brain is defeated in step {} with all items", self.mother_brain_step)];
+        for requirement in [ItemRequirement::Required, ItemRequirement::SoftRequired, ItemRequirement::Optional] {
+            let items: Vec<&RequiredItemResult> = self.items.iter().filter(|x| x.requirement == requirement).collect();
+            lines.push(format!("{}: {} items", requirement.to_string(), items.len()));
+            for result in items {
+                let item_name = Placeable::from_item(result.item).map(|x| x.to_string()).unwrap_or_default();
+                let step_str = match (requirement, result.mother_brain_step) {
+                    (ItemRequirement::SoftRequired, Some(step)) => format!(", Mother Brain in step {step}"),
+                    _ => String::new()
+                };
+                lines.push(format!("    {item_name} ({}){step_str}", get_item_location_name(game_data, result.item_loc)));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct RequiredItemsProgress {
+    pub analyzed: usize,
+    pub total: usize
+}
+
+/// Runs the logic once with all items placed and once for every collected progression item with its location emptied.
+/// All runs share one Logic, so each one resumes from the cached steps of the previous one
+pub(crate) async fn analyze_required_items(
+    game_data: Arc<GameData>,
+    input: DifficultyTierInput,
+    start_location: StartLocation,
+    objectives: Vec<Objective>,
+    item_locations: Vec<Item>,
+    spoiler_overrides: Vec<SpoilerOverride>,
+    spoiler_flag_overrides: Vec<SpoilerFlagOverride>,
+    map: Map,
+    custom_escape_time: Option<usize>,
+    rng_seed: u64,
+    cancel: CancelToken,
+    progress: watch::Sender<RequiredItemsProgress>
+) -> Result<RequiredItemsAnalysis> {
+    let logic = Logic::new(game_data.clone());
+    let start_run = |item_locations: Vec<Item>| {
+        let input = input.clone();
+        logic.update_hub_and_randomization(
+            input.initial_global_state,
+            input.initial_local_state,
+            start_location.clone(),
+            input.locked_door_data,
+            objectives.clone(),
+            input.difficulty,
+            item_locations,
+            spoiler_overrides.clone(),
+            spoiler_flag_overrides.clone(),
+            input.settings,
+            input.difficulty_tiers,
+            map.clone(),
+            custom_escape_time,
+            rng_seed,
+            false,
+            cancel.clone(),
+            watch::channel(SpoilerProgress::default()).0
+        )
+    };
+    // Mother Brain step and whether the escape is logical, None if the logic fails entirely
+    let get_outcome = |res: Result<()>| -> Result<Option<(Option<usize>, bool)>> {
+        if cancel.is_cancelled() {
+            bail!("Required item analysis was cancelled");
+        }
+        Ok(match res {
+            Ok(()) => logic.get_randomization().as_ref().map(|(r, s)| (get_mother_brain_step(&game_data, s), is_escape_logical(r))),
+            Err(_) => None
+        })
+    };
+
+    let res = start_run(item_locations.clone()).await?;
+    let (mother_brain_step, collected) = match get_outcome(res)? {
+        Some((Some(step), true)) => {
+            let lock = logic.get_randomization();
+            (step, get_collected_item_locations(&game_data, &lock.as_ref().unwrap().1))
+        }
+        _ => bail!("The seed has to be beatable with all placed items to find the required ones")
+    };
+
+    let mut item_locs: Vec<usize> = collected.into_iter().filter(|&idx| {
+        let item = item_locations[idx];
+        item.is_unique() || item == Item::Missile || item == Item::Super || item == Item::PowerBomb
+    }).collect();
+    item_locs.sort();
+
+    let total = item_locs.len();
+    progress.send_replace(RequiredItemsProgress { analyzed: 0, total });
+    let mut items = Vec::new();
+    for (analyzed, item_loc) in item_locs.into_iter().enumerate() {
+        let mut locations = item_locations.clone();
+        locations[item_loc] = Item::Nothing;
+        let res = start_run(locations).await?;
+        let (requirement, step) = match get_outcome(res)? {
+            Some((Some(step), true)) if step > mother_brain_step => (ItemRequirement::SoftRequired, Some(step)),
+            Some((Some(step), true)) => (ItemRequirement::Optional, Some(step)),
+            _ => (ItemRequirement::Required, None)
+        };
+        items.push(RequiredItemResult {
+            item_loc,
+            item: item_locations[item_loc],
+            requirement,
+            mother_brain_step: step
+        });
+        progress.send_replace(RequiredItemsProgress { analyzed: analyzed + 1, total });
+    }
+
+    Ok(RequiredItemsAnalysis {
+        mother_brain_step,
+        items
+    })
+}
+
+/// Step an item location is collected in with both skill presets of a comparison, if it differs between them
+pub struct PresetItemDiff {
+    pub item_loc: usize,
+    pub item: Item,
+    pub step_a: Option<usize>, // None if the item is not collected with the first preset
+    pub step_b: Option<usize>,
+    pub new_tech: Vec<String>, // Tech and notables the routes to the item use with the second preset but not with the first
+}
+
+impl PresetItemDiff {
+    /// Collected with only one of the presets
+    pub fn is_unreachable(&self) -> bool {
+        self.step_a.is_none() != self.step_b.is_none()
+    }
+
+    pub fn is_step_changed(&self) -> bool {
+        self.step_a.is_some() && self.step_b.is_some() && self.step_a != self.step_b
+    }
+}
+
+/// Differences between the spoiler logs of the current seed with two skill presets, where the second one is usually the harder one
+pub struct PresetComparison {
+    pub name_a: String,
+    pub name_b: String,
+    pub beatable_a: bool,
+    pub beatable_b: bool,
+    pub items: Vec<PresetItemDiff>, // Ordered by item location
+}
+
+impl PresetComparison {
+    pub fn get_item_diff(&self, item_loc: usize) -> Option<&PresetItemDiff> {
+        self.items.iter().find(|x| x.item_loc == item_loc)
+    }
+
+    pub fn to_string(&self, game_data: &GameData) -> String {
+        let beatable_str = |beatable: bool| if beatable { "beatable" } else { "not beatable" };
+        let mut lines = vec![
+            format!("{}: {}", self.name_a, beatable_str(self.beatable_a)),
+            format!("{}: {}", self.name_b, beatable_str(self.beatable_b))
+        ];
+        if self.items.is_empty() {
+            lines.push("No differences in item steps or tech".to_string());
+        }
+        let step_str = |step: Option<usize>| step.map(|x| format!("step {x}")).unwrap_or("unreachable".to_string());
+        for diff in &self.items {
+            let item_name = Placeable::from_item(diff.item).map(|x| x.to_string()).unwrap_or_default();
+            lines.push(format!("{item_name} ({}): {} -> {}", get_item_location_name(game_data, diff.item_loc), step_str(diff.step_a), step_str(diff.step_b)));
+            if !diff.new_tech.is_empty() {
+                lines.push(format!("    New tech: {}", diff.new_tech.join(", ")));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// First step every item location is collected in, along with the names of the tech and notables required by its obtain and return routes.
+/// Strats that are only one alternative of an Or requirement are left out, as they might not have been used
+fn get_item_steps_and_strats(
+    game_data: &GameData,
+    spoiler_log: &SpoilerLog,
+    strat_reqs: &HashMap<(RoomId, usize), &Requirement>,
+    names: &StratNames
+) -> HashMap<usize, (usize, BTreeSet<String>)> {
+    let item_loc_map: HashMap<(usize, usize), usize> = game_data.item_locations.iter().enumerate().map(|(idx, &loc)| (loc, idx)).collect();
+    let mut res = HashMap::new();
+    for details in &spoiler_log.details {
+        for item in &details.items {
+            let Some(&item_loc) = item_loc_map.get(&(item.location.room_id, item.location.node_id)) else {
+                continue;
+            };
+            if res.contains_key(&item_loc) {
+                continue;
+            }
+            let mut strats = BTreeSet::new();
+            for entry in item.obtain_route.iter().chain(&item.return_route) {
+                let Some(req) = entry.strat_id.and_then(|strat_id| strat_reqs.get(&(entry.room_id, strat_id))) else {
+                    continue;
+                };
+                let mut tech_idxs = Vec::new();
+                let mut notable_idxs = Vec::new();
+                collect_requirement_strats(req, true, &mut tech_idxs, &mut notable_idxs);
+                strats.extend(tech_idxs.into_iter().filter(|x| x.1).filter_map(|(idx, _)| names.tech.get(&game_data.tech_isv.keys[idx])).map(|x| x.0.clone()));
+                strats.extend(notable_idxs.into_iter().filter(|x| x.1).filter_map(|(idx, _)| names.notables.get(&game_data.notable_isv.keys[idx])).map(|x| x.0.clone()));
+            }
+            res.insert(item_loc, (details.step, strats));
+        }
+    }
+    res
+}
+
+/// Runs the logic with both skill presets at the same time and compares the resulting spoiler logs item by item
+pub(crate) async fn compare_presets(
+    game_data: Arc<GameData>,
+    presets: [DifficultyTierInput; 2],
+    start_location: StartLocation,
+    objectives: Vec<Objective>,
+    item_locations: Vec<Item>,
+    spoiler_overrides: Vec<SpoilerOverride>,
+    spoiler_flag_overrides: Vec<SpoilerFlagOverride>,
+    map: Map,
+    custom_escape_time: Option<usize>,
+    rng_seed: u64,
+    names: StratNames
+) -> Result<PresetComparison> {
+    let preset_names: Vec<String> = presets.iter().map(|x| x.name.clone()).collect();
+    let mut runs = Vec::new();
+    for preset in presets {
+        let logic = Logic::new(game_data.clone());
+        let handle = logic.update_hub_and_randomization(
+            preset.initial_global_state,
+            preset.initial_local_state,
+            start_location.clone(),
+            preset.locked_door_data,
+            objectives.clone(),
+            preset.difficulty,
+            item_locations.clone(),
+            spoiler_overrides.clone(),
+            spoiler_flag_overrides.clone(),
+            preset.settings,
+            preset.difficulty_tiers,
+            map.clone(),
+            custom_escape_time,
+            rng_seed,
+            false,
+            CancelToken::new(),
+            watch::channel(SpoilerProgress::default()).0
+        );
+        runs.push((logic, handle));
+    }
+
+    let strat_reqs = get_strat_requirements(&game_data);
+    let mut results = Vec::new();
+    for (logic, handle) in runs {
+        // A preset on which the logic fails entirely collects nothing, like in the minimum difficulty analysis
+        let result = match handle.await? {
+            Ok(()) => match logic.get_randomization().as_ref() {
+                Some((_, s)) => (get_item_steps_and_strats(&game_data, s, &strat_reqs, &names), is_mother_brain_defeated(&game_data, s)),
+                None => (HashMap::new(), false)
+            },
+            Err(_) => (HashMap::new(), false)
+        };
+        results.push(result);
+    }
+    let (steps_b, beatable_b) = results.pop().unwrap();
+    let (steps_a, beatable_a) = results.pop().unwrap();
+
+    let mut item_locs: Vec<usize> = steps_a.keys().chain(steps_b.keys()).copied().collect::<HashSet<_>>().into_iter().collect();
+    item_locs.sort();
+    let mut items = Vec::new();
+    for item_loc in item_locs {
+        let a = steps_a.get(&item_loc);
+        let b = steps_b.get(&item_loc);
+        let new_tech: Vec<String> = match (a, b) {
+            (Some((_, strats_a)), Some((_, strats_b))) => strats_b.difference(strats_a).cloned().collect(),
+            _ => Vec::new()
+        };
+        let diff = PresetItemDiff {
+            item_loc,
+            item: item_locations[item_loc],
+            step_a: a.map(|x| x.0),
+            step_b: b.map(|x| x.0),
+            new_tech
+        };
+        if diff.is_unreachable() || diff.is_step_changed() || !diff.new_tech.is_empty() {
+            items.push(diff);
+        }
+    }
+
+    Ok(PresetComparison {
+        name_a: preset_names[0].clone(),
+        name_b: preset_names[1].clone(),
+        beatable_a,
+        beatable_b,
+        items
+    })
+}
+
+/// Name and difficulty of every tech and notable, taken from the preset data
+#[derive(Clone)]
+pub(crate) struct StratNames {
+    pub(crate) tech: HashMap<TechId, (String, String)>,
+    pub(crate) notables: HashMap<(RoomId, NotableId), (String, String)>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StratUsage {
+    pub step: Option<usize>, // None for the routes to and from the hub location
+    pub target: String, // Item or flag the route leads to
+    pub room: String,
+    pub strat: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TechUsage {
+    pub name: String,
+    pub difficulty: String,
+    pub uses: Vec<StratUsage>,
+}
+
+/// Tech and notable strats that are used in the routes of the spoiler log, ordered by their first use.
+/// Strats that only appear as an alternative of an Or requirement are listed separately, as they might not have been used
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct TechReport {
+    pub tech: Vec<TechUsage>,
+    pub notables: Vec<TechUsage>,
+    pub possible_tech: Vec<TechUsage>,
+    pub possible_notables: Vec<TechUsage>,
+}
+
+/// Collects the tech and notables of a requirement along with whether they are required. Alternatives of an Or requirement
+/// are only required if every alternative needs them, as the spoiler log does not tell which one was taken
+fn collect_requirement_strats(req: &Requirement, required: bool, tech: &mut Vec<(usize, bool)>, notables: &mut Vec<(usize, bool)>) {
+    match req {
+        Requirement::Tech(tech_idx) => tech.push((*tech_idx, required)),
+        Requirement::Notable(notable_idx) => notables.push((*notable_idx, required)),
+        Requirement::And(reqs) => {
+            for req in reqs {
+                collect_requirement_strats(req, required, tech, notables);
+            }
+        }
+        Requirement::Or(reqs) => {
+            let mut branch_tech = Vec::new();
+            let mut branch_notables = Vec::new();
+            for req in reqs {
+                let mut alt_tech = Vec::new();
+                let mut alt_notables = Vec::new();
+                collect_requirement_strats(req, required, &mut alt_tech, &mut alt_notables);
+                branch_tech.push(alt_tech);
+                branch_notables.push(alt_notables);
+            }
+            push_or_branches(&branch_tech, tech);
+            push_or_branches(&branch_notables, notables);
+        }
+        _ => {}
+    }
+}
+
+fn push_or_branches(branches: &[Vec<(usize, bool)>], out: &mut Vec<(usize, bool)>) {
+    for branch in branches {
+        for &(idx, required) in branch {
+            let in_every_branch = branches.iter().all(|x| x.contains(&(idx, true)));
+            out.push((idx, required && in_every_branch));
+        }
+    }
+}
+
+fn add_usage(usages: &mut Vec<TechUsage>, (name, difficulty): &(String, String), usage: StratUsage) {
+    match usages.iter_mut().find(|x| x.name == *name) {
+        Some(tech_usage) => tech_usage.uses.push(usage),
+        None => usages.push(TechUsage {
+            name: name.clone(),
+            difficulty: difficulty.clone(),
+            uses: vec![usage]
+        })
+    }
+}
+
+/// Requirements of every strat, keyed by (room_id, strat_id) as referenced by SpoilerRouteEntry
+pub(crate) fn get_strat_requirements(game_data: &GameData) -> HashMap<(RoomId, usize), &Requirement> {
+    let mut strat_reqs: HashMap<(RoomId, usize), &Requirement> = HashMap::new();
+    for link in &game_data.links {
+        if let Some(strat_id) = link.strat_id {
+            let room_id = game_data.vertex_isv.keys[link.from_vertex_id].room_id;
+            strat_reqs.entry((room_id, strat_id)).or_insert(&link.requirement);
+        }
+    }
+    strat_reqs
+}
+
+pub(crate) fn get_tech_report(game_data: &GameData, spoiler_log: &SpoilerLog, hub_data: &HubLocationData, names: &StratNames) -> TechReport {
+    let strat_reqs = get_strat_requirements(game_data);
+
+    let mut routes: Vec<(Option<usize>, String, &Vec<SpoilerRouteEntry>)> = vec![
+        (None, "Hub".to_string(), &hub_data.hub_obtain_route),
+        (None, "Hub".to_string(), &hub_data.hub_return_route)
+    ];
+    for details in &spoiler_log.details {
+        for item in &details.items {
+            routes.push((Some(details.step), item.item.clone(), &item.obtain_route));
+            routes.push((Some(details.step), item.item.clone(), &item.return_route));
+        }
+        for flag in &details.flags {
+            routes.push((Some(details.step), flag.flag.clone(), &flag.obtain_route));
+            routes.push((Some(details.step), flag.flag.clone(), &flag.return_route));
+        }
+    }
+
+    let mut report = TechReport::default();
+    for (step, target, route) in routes {
+        for entry in route {
+            let Some(req) = entry.strat_id.and_then(|strat_id| strat_reqs.get(&(entry.room_id, strat_id))) else {
+                continue;
+            };
+            let mut tech_idxs = Vec::new();
+            let mut notable_idxs = Vec::new();
+            collect_requirement_strats(req, true, &mut tech_idxs, &mut notable_idxs);
+
+            let usage = StratUsage {
+                step,
+                target: target.clone(),
+                room: entry.room.clone(),
+                strat: entry.strat_name.clone()
+            };
+            for (tech_idx, required) in tech_idxs {
+                if let Some(name) = names.tech.get(&game_data.tech_isv.keys[tech_idx]) {
+                    let usages = if required { &mut report.tech } else { &mut report.possible_tech };
+                    add_usage(usages, name, usage.clone());
+                }
+            }
+            for (notable_idx, required) in notable_idxs {
+                if let Some(name) = names.notables.get(&game_data.notable_isv.keys[notable_idx]) {
+                    let usages = if required { &mut report.notables } else { &mut report.possible_notables };
+                    add_usage(usages, name, usage.clone());
+                }
+            }
+        }
+    }
+
+    // Strats that are required somewhere are only listed as required
+    let tech_names: HashSet<String> = report.tech.iter().map(|x| x.name.clone()).collect();
+    report.possible_tech.retain(|x| !tech_names.contains(&x.name));
+    let notable_names: HashSet<String> = report.notables.iter().map(|x| x.name.clone()).collect();
+    report.possible_notables.retain(|x| !notable_names.contains(&x.name));
+
+    report
+}
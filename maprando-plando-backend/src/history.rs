@@ -0,0 +1,189 @@
+use std::fmt;
+
+use maprando::randomize::LockedDoor;
+use maprando_game::{Item, StartLocation};
+use strum::VariantArray;
+
+use crate::{map_editor::MapEditorSnapshot, Placeable, SpoilerOverride};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    PlaceItem,
+    PlaceDoor,
+    PlaceStartLocation,
+    EraseRoom,
+    SpawnRoom,
+    MoveRooms,
+    ApplyArea,
+    SwapAreas,
+    ClearItems,
+    ClearDoors,
+    FillItems,
+    MergeSeeds,
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Command::PlaceItem => "Place Item",
+            Command::PlaceDoor => "Place Door",
+            Command::PlaceStartLocation => "Place Start Location",
+            Command::EraseRoom => "Erase Room",
+            Command::SpawnRoom => "Spawn Room",
+            Command::MoveRooms => "Move Rooms",
+            Command::ApplyArea => "Apply Area",
+            Command::SwapAreas => "Swap Areas",
+            Command::ClearItems => "Clear Items",
+            Command::ClearDoors => "Clear Doors",
+            Command::FillItems => "Fill Items",
+            Command::MergeSeeds => "Merge Seeds",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// All state of a Plando that can be changed by a command
+#[derive(Clone)]
+pub(crate) struct PlandoSnapshot {
+    pub(crate) map_editor: MapEditorSnapshot,
+    pub(crate) item_locations: Vec<Item>,
+    pub(crate) placed_item_count: [usize; Placeable::VARIANTS.len()],
+    pub(crate) start_location: StartLocation,
+    pub(crate) locked_doors: Vec<LockedDoor>,
+    pub(crate) door_lock_loc: Vec<(usize, usize, usize)>,
+    pub(crate) door_beam_loc: Vec<(usize, usize, usize)>,
+    pub(crate) total_door_count: usize,
+    pub(crate) spoiler_overrides: Vec<SpoilerOverride>,
+}
+
+impl PlandoSnapshot {
+    fn is_same(&self, other: &PlandoSnapshot) -> bool {
+        let same_start = self.start_location.room_id == other.start_location.room_id
+            && self.start_location.node_id == other.start_location.node_id
+            && self.start_location.x == other.start_location.x
+            && self.start_location.y == other.start_location.y;
+        let same_doors = self.locked_doors.len() == other.locked_doors.len() && self.locked_doors.iter().zip(other.locked_doors.iter()).all(|(a, b)| {
+            a.src_ptr_pair == b.src_ptr_pair && a.dst_ptr_pair == b.dst_ptr_pair && a.door_type == b.door_type
+        });
+
+        same_start && same_doors
+            && self.item_locations == other.item_locations
+            && self.spoiler_overrides == other.spoiler_overrides
+            && self.map_editor.is_same_map(&other.map_editor)
+    }
+}
+
+pub(crate) struct HistoryEntry {
+    pub(crate) command: Command,
+    pub(crate) state: PlandoSnapshot,
+}
+
+/// Log of reversible commands. Every entry stores the state before (undo stack) or after (redo stack) the command was executed.
+/// Commands issued while another command is still running are recorded as part of the outer command.
+pub struct History {
+    undo_stack: Vec<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    pending: Option<HistoryEntry>,
+    depth: usize,
+}
+
+impl History {
+    pub const MAX_ENTRIES: usize = 256;
+
+    pub fn new() -> Self {
+        History {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            pending: None,
+            depth: 0
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.pending = None;
+        self.depth = 0;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.depth == 0 && !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.depth == 0 && !self.redo_stack.is_empty()
+    }
+
+    pub fn next_undo(&self) -> Option<Command> {
+        self.undo_stack.last().map(|entry| entry.command)
+    }
+
+    pub fn next_redo(&self) -> Option<Command> {
+        self.redo_stack.last().map(|entry| entry.command)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.depth > 0
+    }
+
+    pub(crate) fn begin(&mut self, command: Command, state: Option<PlandoSnapshot>) {
+        if self.depth == 0 {
+            self.pending = state.map(|state| HistoryEntry { command, state });
+        }
+        self.depth += 1;
+    }
+
+    /// Finishes the current command. Returns the pending entry once the outermost command has finished
+    pub(crate) fn end(&mut self) -> Option<HistoryEntry> {
+        if self.depth == 0 {
+            return None;
+        }
+        self.depth -= 1;
+        if self.depth > 0 {
+            return None;
+        }
+        self.pending.take()
+    }
+
+    /// Records a finished command. Returns false if the command did not change anything, e.g. clicking a room without moving it
+    pub(crate) fn record(&mut self, entry: HistoryEntry, cur_state: &PlandoSnapshot) -> bool {
+        if entry.state.is_same(cur_state) {
+            return false;
+        }
+
+        self.undo_stack.push(entry);
+        if self.undo_stack.len() > Self::MAX_ENTRIES {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+        true
+    }
+
+    pub(crate) fn pop_undo(&mut self) -> Option<HistoryEntry> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.undo_stack.pop()
+    }
+
+    pub(crate) fn pop_redo(&mut self) -> Option<HistoryEntry> {
+        if !self.can_redo() {
+            return None;
+        }
+        self.redo_stack.pop()
+    }
+
+    pub(crate) fn push_undo(&mut self, entry: HistoryEntry) {
+        self.undo_stack.push(entry);
+    }
+
+    pub(crate) fn push_redo(&mut self, entry: HistoryEntry) {
+        self.redo_stack.push(entry);
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
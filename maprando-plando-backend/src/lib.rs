@@ -2,21 +2,26 @@ pub mod logic;
 pub mod randomize;
 pub mod seed_data;
 pub mod map_editor;
+pub mod history;
+pub mod analysis;
+pub mod progression_graph;
+pub mod seed_merge;
+pub mod spoiler_export;
 
 use std::{path::Path, sync::{Arc, MutexGuard}};
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use hashbrown::{HashMap, HashSet};
-use maprando::{customize::{CustomizeSettings, mosaic::MosaicTheme, samus_sprite::SamusSpriteCategory}, patch::Rom, preset::PresetData, randomize::{DifficultyConfig, LockedDoor, Randomization}, settings::{DoorsSettings, ItemCount, Objective, RandomizerSettings, SpeedBooster, WallJump}, spoiler_log::SpoilerLog, traverse::LockedDoorData};
+use maprando::{customize::{CustomizeSettings, mosaic::MosaicTheme, samus_sprite::SamusSpriteCategory}, patch::Rom, preset::PresetData, randomize::{DifficultyConfig, LockedDoor, Randomization}, settings::{DoorsSettings, ItemCount, Objective, ObjectiveSetting, RandomizerSettings, SkillAssumptionSettings, SpeedBooster, WallJump}, spoiler_log::SpoilerLog, traverse::LockedDoorData};
 use maprando_game::{BeamType, Capacity, DoorPtrPair, DoorType, GameData, HubLocation, Item, Map, NodeId, RoomId, StartLocation, VertexKey};
 use maprando_logic::{GlobalState, Inventory, LocalState};
-use rand::{rngs::StdRng, SeedableRng};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
 use strum::VariantArray;
 use strum_macros::VariantArray;
-use tokio::task::JoinHandle;
+use tokio::{sync::watch, task::JoinHandle};
 
-use crate::{logic::{HubLocationData, Logic}, map_editor::{MapEditor, MapErrorType}, randomize::{get_gray_doors, get_randomizable_doors}};
+use crate::{analysis::{DifficultyAnalysis, DifficultyTierInput, PresetComparison, RequiredItemsAnalysis, RequiredItemsProgress, StratNames, TechReport}, history::{Command, History, HistoryEntry, PlandoSnapshot}, logic::{CancelToken, HubLocationData, Logic, LogicTimings, ReachabilityQuery, ReachabilityResult, SoftLock, SpoilerProgress}, map_editor::{Area, MapEditor, MapErrorType}, progression_graph::ProgressionGraph, randomize::{get_gray_doors, get_randomizable_doors}, spoiler_export::SpoilerReport};
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum DoubleItemPlacement {
@@ -200,15 +205,41 @@ impl Placeable {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct SpoilerOverride {
     pub step: usize,
     pub item_idx: usize,
     pub description: String
 }
 
+/// Marks a flag, e.g. a boss kill, as obtained at a step regardless of whether it is logically reachable
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct SpoilerFlagOverride {
+    pub step: usize,
+    pub flag_id: usize,
+    pub description: String
+}
+
+/// Item location or flag a spoiler override applies to
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub enum OverrideTarget {
+    Item(usize), // item_idx
+    Flag(usize) // flag_id
+}
+
+impl OverrideTarget {
+    pub fn to_string(&self, game_data: &GameData) -> String {
+        match self {
+            OverrideTarget::Item(item_idx) => analysis::get_item_location_name(game_data, *item_idx),
+            OverrideTarget::Flag(flag_id) => game_data.flag_isv.keys[*flag_id].clone()
+        }
+    }
+}
+
 struct ImplicitPresetData {
     difficulty_tiers: Vec<DifficultyConfig>,
+    skill_presets: Vec<SkillAssumptionSettings>,
+    strat_names: StratNames,
     implicit_tech: Vec<i32>,
     implicit_notables: Vec<(usize, usize)>,
 }
@@ -219,6 +250,7 @@ pub struct Plando {
     pub map_editor: MapEditor,
     pub randomizer_settings: RandomizerSettings,
     pub objectives: Vec<Objective>,
+    pub custom_objectives: Option<Vec<Objective>>, // Objectives picked by the author. If None, objectives are rolled from the objective settings
     pub item_locations: Vec<Item>,
     pub start_location: StartLocation,
     pub placed_item_count: [usize; Placeable::VARIANTS.len()],
@@ -226,22 +258,32 @@ pub struct Plando {
     pub locked_doors: Vec<LockedDoor>,
     pub gray_doors: HashSet<DoorPtrPair>,
     pub spoiler_overrides: Vec<SpoilerOverride>,
+    pub spoiler_flag_overrides: Vec<SpoilerFlagOverride>,
     pub custom_escape_time: Option<usize>,
     pub creator_name: String,
+    pub item_pool: Option<Vec<ItemCount>>,
+    pub history: History,
+    pub rng_seed: u64, // Seeds all randomness of the spoiler and patched ROM, so they can be reproduced from the seed file
 
     door_lock_loc: Vec<(usize, usize, usize)>,
     door_beam_loc: Vec<(usize, usize, usize)>,
     total_door_count: usize,
     preset_data: ImplicitPresetData,
+    edit_count: usize, // Increased whenever a command, undo, redo or loaded map changes the seed
 
     logic: Logic,
+    spoiler_cancel: CancelToken,
+    spoiler_progress: watch::Receiver<SpoilerProgress>,
+    required_items_cancel: CancelToken,
+    required_items_progress: watch::Receiver<RequiredItemsProgress>,
 
     pub rng: StdRng
 }
 
 impl Plando {
     pub fn new(game_data: Arc<GameData>, map: Map, preset_data: &PresetData, toilet_path: &Path) -> Result<Self> {
-        let mut rng = rand::rngs::StdRng::from_entropy();
+        let rng_seed = StdRng::from_entropy().next_u64();
+        let mut rng = StdRng::seed_from_u64(rng_seed);
 
         let map_editor = MapEditor::new(map, game_data.clone(), toilet_path);
 
@@ -258,6 +300,15 @@ impl Plando {
 
         let impl_preset_data = ImplicitPresetData {
             difficulty_tiers: preset_data.difficulty_tiers.clone(),
+            skill_presets: preset_data.skill_presets.clone(),
+            strat_names: StratNames {
+                tech: preset_data.tech_data_map.iter().map(|(&tech_id, data)| {
+                    (tech_id, (data.name.clone(), data.difficulty.clone()))
+                }).collect(),
+                notables: preset_data.notable_data_map.iter().map(|(&id_pair, data)| {
+                    (id_pair, (format!("{}: {}", data.room_name, data.name), data.difficulty.clone()))
+                }).collect()
+            },
             implicit_tech: preset_data.tech_by_difficulty["Implicit"].clone(),
             implicit_notables: preset_data.notables_by_difficulty["Implicit"].clone()
         };
@@ -268,6 +319,7 @@ impl Plando {
             map_editor,
             randomizer_settings,
             objectives,
+            custom_objectives: None,
             item_locations: vec![Item::Nothing; item_location_len],
             start_location: ship_start,
             placed_item_count,
@@ -275,14 +327,23 @@ impl Plando {
             locked_doors: Vec::new(),
             gray_doors: get_gray_doors(),
             spoiler_overrides: Vec::new(),
+            spoiler_flag_overrides: Vec::new(),
             custom_escape_time: None,
             creator_name: "Plando".to_string(),
+            item_pool: None,
+            history: History::new(),
+            rng_seed,
 
             door_lock_loc: Vec::new(),
             door_beam_loc: Vec::new(),
             total_door_count: 0,
             preset_data: impl_preset_data,
+            edit_count: 0,
             logic: Logic::new(game_data),
+            spoiler_cancel: CancelToken::new(),
+            spoiler_progress: watch::channel(SpoilerProgress::default()).1,
+            required_items_cancel: CancelToken::new(),
+            required_items_progress: watch::channel(RequiredItemsProgress::default()).1,
 
             rng
         };
@@ -305,6 +366,7 @@ impl Plando {
     }
 
     pub fn clear_item_locations(&mut self) {
+        self.begin_command(Command::ClearItems);
         for i in 0..self.item_locations.len() {
             self.item_locations[i] = Item::Nothing;
         }
@@ -312,6 +374,77 @@ impl Plando {
             self.placed_item_count[i] = 0;
         }
         self.spoiler_overrides.clear();
+        self.end_command();
+    }
+
+    /// Starts recording a command into the history. Every call has to be matched by a call to end_command.
+    /// Nested commands are recorded as part of the outermost command
+    pub fn begin_command(&mut self, command: Command) {
+        let state = if self.history.is_recording() { None } else { Some(self.snapshot()) };
+        self.history.begin(command, state);
+    }
+
+    pub fn end_command(&mut self) {
+        if let Some(entry) = self.history.end() {
+            let cur_state = self.snapshot();
+            if self.history.record(entry, &cur_state) {
+                self.edit_count += 1;
+            }
+            // Placed item counts may have changed, the item pool warnings would be stale until the next spoiler update otherwise
+            self.check_item_pool();
+        }
+    }
+
+    /// Changes whenever the items, doors or map are edited, so results computed for an older state can be told apart
+    pub fn get_edit_count(&self) -> usize {
+        self.edit_count
+    }
+
+    pub fn undo(&mut self) -> Option<Command> {
+        let entry = self.history.pop_undo()?;
+        let command = entry.command;
+        let state = self.snapshot();
+        self.history.push_redo(HistoryEntry { command, state });
+        self.restore(entry.state);
+        self.edit_count += 1;
+        Some(command)
+    }
+
+    pub fn redo(&mut self) -> Option<Command> {
+        let entry = self.history.pop_redo()?;
+        let command = entry.command;
+        let state = self.snapshot();
+        self.history.push_undo(HistoryEntry { command, state });
+        self.restore(entry.state);
+        self.edit_count += 1;
+        Some(command)
+    }
+
+    fn snapshot(&self) -> PlandoSnapshot {
+        PlandoSnapshot {
+            map_editor: self.map_editor.snapshot(),
+            item_locations: self.item_locations.clone(),
+            placed_item_count: self.placed_item_count,
+            start_location: self.start_location.clone(),
+            locked_doors: self.locked_doors.clone(),
+            door_lock_loc: self.door_lock_loc.clone(),
+            door_beam_loc: self.door_beam_loc.clone(),
+            total_door_count: self.total_door_count,
+            spoiler_overrides: self.spoiler_overrides.clone()
+        }
+    }
+
+    fn restore(&mut self, state: PlandoSnapshot) {
+        self.map_editor.restore(state.map_editor);
+        self.item_locations = state.item_locations;
+        self.placed_item_count = state.placed_item_count;
+        self.start_location = state.start_location;
+        self.locked_doors = state.locked_doors;
+        self.door_lock_loc = state.door_lock_loc;
+        self.door_beam_loc = state.door_beam_loc;
+        self.total_door_count = state.total_door_count;
+        self.spoiler_overrides = state.spoiler_overrides;
+        self.check_item_pool();
     }
 
     pub fn room_id_to_idx(&self, id: usize) -> usize {
@@ -329,13 +462,19 @@ impl Plando {
         None
     }
 
+    /// Loads a new map with no items or doors placed. Clears the history, unless the map is loaded as part of a command like merging seeds
     pub fn load_map(&mut self, map: Map) {
         self.map_editor.load_map(map);
         self.clear_item_locations();
         self.clear_doors();
         self.start_location = Plando::get_ship_start();
+        self.spoiler_flag_overrides.clear();
         self.update_randomizable_doors();
         self.logic.reset();
+        if !self.history.is_recording() {
+            self.history.clear();
+        }
+        self.edit_count += 1;
     }
 
     pub fn load_map_from_file(&mut self, path: &Path) -> Result<()> {
@@ -343,8 +482,11 @@ impl Plando {
         self.clear_item_locations();
         self.clear_doors();
         self.start_location = Plando::get_ship_start();
+        self.spoiler_flag_overrides.clear();
         self.update_randomizable_doors();
         self.logic.reset();
+        self.history.clear();
+        self.edit_count += 1;
         Ok(())
     }
 
@@ -353,6 +495,13 @@ impl Plando {
             bail!("Map has errors that need to be fixed");
         }
 
+        self.begin_command(Command::PlaceDoor);
+        let res = self.place_wall_doors();
+        self.end_command();
+        res
+    }
+
+    fn place_wall_doors(&mut self) -> Result<()> {
         for door in self.map_editor.error_list.clone() {
             if let MapErrorType::DoorDisconnected(room_idx, door_idx) = door {
                 if let Err(e) = self.place_door(room_idx, door_idx, Some(DoorType::Wall), false) {
@@ -404,11 +553,34 @@ impl Plando {
 
     pub fn load_preset(&mut self, preset: RandomizerSettings) {
         self.randomizer_settings = preset;
-        self.objectives = maprando::randomize::get_objectives(&self.randomizer_settings, Some(self.map_editor.get_map()), &self.game_data, &mut self.rng);
-        self.update_randomizable_doors();
+        self.update_objectives();
         self.get_difficulty_tiers();
     }
 
+    /// Sets the objectives picked by the author. None rolls them from the objective settings instead
+    pub fn set_objectives(&mut self, objectives: Option<Vec<Objective>>) {
+        self.custom_objectives = objectives;
+        self.update_objectives();
+    }
+
+    /// Restarts the RNG from the given seed. Objectives are only rolled again on the next call to update_objectives
+    pub fn set_rng_seed(&mut self, rng_seed: u64) {
+        self.rng_seed = rng_seed;
+        self.rng = StdRng::seed_from_u64(rng_seed);
+    }
+
+    /// Rolls the objectives from a fresh RNG seeded with `rng_seed`, so the same settings always give the same objectives
+    pub fn update_objectives(&mut self) {
+        self.objectives = match &self.custom_objectives {
+            Some(objectives) => objectives.clone(),
+            None => {
+                let mut rng = StdRng::seed_from_u64(self.rng_seed);
+                maprando::randomize::get_objectives(&self.randomizer_settings, Some(self.map_editor.get_map()), &self.game_data, &mut rng)
+            }
+        };
+        self.update_randomizable_doors();
+    }
+
     pub fn update_randomizable_doors(&mut self) {
         self.randomizable_doors = get_randomizable_doors(&self.game_data, &self.objectives);
     }
@@ -454,6 +626,8 @@ impl Plando {
             return;
         }
 
+        self.begin_command(Command::EraseRoom);
+
         // Clear all items in the room so they can be placed again
         for item_idx in 0..self.item_locations.len() {
             if self.item_locations[item_idx] != Item::Nothing && self.game_data.item_locations[item_idx].0 == room_id {
@@ -473,13 +647,66 @@ impl Plando {
         }
 
         self.map_editor.erase_room(room_idx, &self.locked_doors);
+        self.end_command();
+    }
+
+    pub fn spawn_room(&mut self, room_idx: usize) {
+        self.begin_command(Command::SpawnRoom);
+        self.map_editor.spawn_room(room_idx, &self.locked_doors);
+        self.end_command();
+    }
+
+    pub fn move_room(&mut self, room_idx: usize, x: usize, y: usize) {
+        self.begin_command(Command::MoveRooms);
+        self.map_editor.move_room(room_idx, x, y);
+        self.end_command();
+    }
+
+    pub fn snap_room(&mut self, room_idx: usize) {
+        self.begin_command(Command::MoveRooms);
+        self.map_editor.snap_room(room_idx, &self.locked_doors);
+        self.end_command();
+    }
+
+    pub fn apply_area(&mut self, room_idx: usize, area_value: Area) {
+        self.begin_command(Command::ApplyArea);
+        self.map_editor.apply_area(room_idx, area_value, &self.locked_doors);
+        self.end_command();
+    }
+
+    pub fn swap_areas(&mut self, area1: usize, area2: usize) {
+        self.begin_command(Command::SwapAreas);
+        self.map_editor.swap_areas(area1, area2, &self.locked_doors);
+        self.end_command();
+    }
+
+    /// Re-places all door locks after the map has changed, dropping locks that are no longer valid
+    pub fn sync_doors(&mut self) {
+        self.begin_command(Command::PlaceDoor);
+        self.update_randomizable_doors();
+
+        let door_locks = self.locked_doors.clone();
+        self.clear_doors();
+        for door in door_locks {
+            let (room_idx, door_idx) = self.game_data.room_and_door_idxs_by_door_ptr_pair[&door.src_ptr_pair];
+            if self.place_door(room_idx, door_idx, Some(door.door_type), false).is_err() {
+                // TODO: Potentially Notify user?
+            }
+        }
+        self.end_command();
     }
 
     pub fn place_start_location(&mut self, start_loc: StartLocation) {
+        self.begin_command(Command::PlaceStartLocation);
         self.start_location = start_loc;
+        self.end_command();
     }
 
     pub fn place_item(&mut self, item_loc: usize, item: Item) {
+        if self.item_locations[item_loc] == item {
+            return;
+        }
+        self.begin_command(Command::PlaceItem);
         // Remove old item from placed_item_count
         if self.item_locations[item_loc] != Item::Nothing {
             let old_placeable = Placeable::from_item(self.item_locations[item_loc]).unwrap();
@@ -491,9 +718,17 @@ impl Plando {
             self.placed_item_count[placeable as usize] += 1;
         }
         self.item_locations[item_loc] = item;
+        self.end_command();
     }
 
     pub fn place_door(&mut self, room_idx: usize, door_idx: usize, door_type_opt: Option<DoorType>, replace: bool) -> Result<()> {
+        self.begin_command(Command::PlaceDoor);
+        let res = self.place_door_impl(room_idx, door_idx, door_type_opt, replace);
+        self.end_command();
+        res
+    }
+
+    fn place_door_impl(&mut self, room_idx: usize, door_idx: usize, door_type_opt: Option<DoorType>, replace: bool) -> Result<()> {
         let door = &self.game_data.room_geometry[room_idx].doors[door_idx];
         let ptr_pair = (door.exit_ptr, door.entrance_ptr);
 
@@ -620,6 +855,7 @@ impl Plando {
     }
 
     pub fn clear_doors(&mut self) {
+        self.begin_command(Command::ClearDoors);
         self.door_beam_loc.clear();
         self.door_lock_loc.clear();
         self.locked_doors.clear();
@@ -628,6 +864,7 @@ impl Plando {
         for i in Placeable::DoorMissile as usize..=Placeable::DoorWall as usize {
             self.placed_item_count[i] = 0;
         }
+        self.end_command();
     }
 
     pub fn get_max_placeable_count(&self, placeable: Placeable) -> Option<usize> {
@@ -669,6 +906,62 @@ impl Plando {
         None
     }
 
+    /// Target count of the placeable in the target item pool, if the seed declares one
+    pub fn get_target_item_count(&self, placeable: Placeable) -> Option<usize> {
+        let item = placeable.to_item()?;
+        let item_pool = self.item_pool.as_ref()?;
+        Some(item_pool.iter().find(|x| x.item == item).map(|x| x.count).unwrap_or_default())
+    }
+
+    pub fn set_target_item_count(&mut self, placeable: Placeable, count: usize) {
+        let Some(item) = placeable.to_item() else {
+            return;
+        };
+        let item_pool = self.item_pool.get_or_insert_with(Vec::new);
+        match item_pool.iter_mut().find(|x| x.item == item) {
+            Some(item_count) => item_count.count = count,
+            None => item_pool.push(ItemCount { item, count })
+        }
+    }
+
+    /// Sets the target item pool to the items that are currently placed
+    pub fn set_item_pool_from_placements(&mut self) {
+        self.item_pool = Some(Placeable::VARIANTS[Placeable::ETank as usize..=Placeable::SparkBooster as usize].iter().map(|&placeable| {
+            ItemCount {
+                item: placeable.to_item().unwrap(),
+                count: self.placed_item_count[placeable as usize]
+            }
+        }).collect());
+    }
+
+    /// Compares the placed items against the target item pool
+    pub fn check_item_pool(&mut self) {
+        self.map_editor.error_list.retain(|x| match x {
+            MapErrorType::ItemMissing(_, _, _) | MapErrorType::ItemSurplus(_, _, _) | MapErrorType::ProgressionItemZero(_) => false,
+            _ => true
+        });
+
+        if self.item_pool.is_none() {
+            return;
+        }
+
+        for &placeable in &Placeable::VARIANTS[Placeable::ETank as usize..=Placeable::SparkBooster as usize] {
+            let target = self.get_target_item_count(placeable).unwrap();
+            let placed = self.placed_item_count[placeable as usize];
+            if placed < target {
+                self.map_editor.error_list.push(MapErrorType::ItemMissing(placeable, placed, target));
+            } else if placed > target {
+                self.map_editor.error_list.push(MapErrorType::ItemSurplus(placeable, placed, target));
+            }
+
+            let item = placeable.to_item().unwrap();
+            let is_progression = item.is_unique() || item == Item::Missile || item == Item::Super || item == Item::PowerBomb;
+            if is_progression && target == 0 && self.get_max_placeable_count(placeable).is_none_or(|x| x > 0) {
+                self.map_editor.error_list.push(MapErrorType::ProgressionItemZero(placeable));
+            }
+        }
+    }
+
     pub fn get_locked_door_data(&self) -> LockedDoorData {
         let mut locked_door_node_map: HashMap<(RoomId, NodeId), usize> = HashMap::new();
         for (i, door) in self.locked_doors.iter().enumerate() {
@@ -713,6 +1006,17 @@ impl Plando {
         self.spoiler_overrides.retain(|x| {
             self.item_locations[x.item_idx] != Item::Nothing
         });
+
+        // Flag ids are indices into the game data flags, drop overrides of flags that don't exist and warn about them
+        let flag_count = self.game_data.flag_isv.keys.len();
+        let error_list = &mut self.map_editor.error_list;
+        self.spoiler_flag_overrides.retain(|x| {
+            let exists = x.flag_id < flag_count;
+            if !exists {
+                error_list.push(MapErrorType::OverrideFlagMissing(x.step));
+            }
+            exists
+        });
     }
 
     pub fn is_map_logic_valid(&self) -> Result<()> {
@@ -790,6 +1094,18 @@ impl Plando {
             spazer_doors_count,
             plasma_doors_count
         };
+
+        // Objectives picked by the author overwrite the objective options, so the patched ROM and spoiler settings match them
+        if let Some(objectives) = &self.custom_objectives {
+            self.objectives = objectives.clone();
+            let objective_settings = &mut settings.objective_settings;
+            for option in &mut objective_settings.objective_options {
+                option.setting = if objectives.contains(&option.objective) { ObjectiveSetting::Yes } else { ObjectiveSetting::No };
+            }
+            objective_settings.min_objectives = objectives.len() as i32;
+            objective_settings.max_objectives = objectives.len() as i32;
+            objective_settings.preset = None;
+        }
     }
 
     pub fn update_spoiler_data(&mut self, rebuild_steps: bool) -> Result<JoinHandle<Result<()>>> {
@@ -811,7 +1127,13 @@ impl Plando {
             &implicit_notables,
         );
 
-        let (initial_global_state, initial_local_state) = self.get_initial_states();
+        let (initial_global_state, initial_local_state) = self.get_initial_states(&self.difficulty_tiers[0].tech);
+
+        // Only the latest computation is of interest
+        self.spoiler_cancel.cancel();
+        self.spoiler_cancel = CancelToken::new();
+        let (progress_tx, progress_rx) = watch::channel(SpoilerProgress::default());
+        self.spoiler_progress = progress_rx;
 
         let handle = self.logic.update_hub_and_randomization(
             initial_global_state,
@@ -822,32 +1144,411 @@ impl Plando {
             difficulty,
             self.item_locations.clone(),
             self.spoiler_overrides.clone(),
+            self.spoiler_flag_overrides.clone(),
             self.randomizer_settings.clone(),
             self.difficulty_tiers.clone(),
             self.map().clone(),
             self.custom_escape_time.clone(),
-            rebuild_steps
+            self.rng_seed,
+            rebuild_steps,
+            self.spoiler_cancel.clone(),
+            progress_tx
         );
         
         Ok(handle)
     }
 
+    /// Stops the computation started by the last call to update_spoiler_data. Its handle returns an error
+    pub fn cancel_spoiler_update(&self) {
+        self.spoiler_cancel.cancel();
+    }
+
+    /// Progress of the computation started by the last call to update_spoiler_data
+    pub fn get_spoiler_progress(&self) -> SpoilerProgress {
+        *self.spoiler_progress.borrow()
+    }
+
+    /// Timing of the last finished spoiler computation and of the last one which had to start from scratch
+    pub fn get_logic_timings(&self) -> LogicTimings {
+        self.logic.get_timings()
+    }
+
+    /// Item pool of a regular seed on the current map: Every unique item that can still be placed, all tanks,
+    /// 10 Supers and 10 Power Bombs. Missiles make up the remaining item locations
+    pub fn get_default_item_pool(&self) -> Vec<ItemCount> {
+        let map = self.map();
+        let item_loc_count = self.game_data.item_locations.iter().filter(|(room_id, _)| {
+            let room_idx = self.room_id_to_idx(*room_id);
+            map.room_mask[room_idx]
+        }).count();
+
+        let mut pool: Vec<ItemCount> = Vec::new();
+        for &placeable in &Placeable::VARIANTS[Placeable::ETank as usize..=Placeable::SparkBooster as usize] {
+            let count = match placeable {
+                Placeable::Missile => continue,
+                Placeable::SuperMissile | Placeable::PowerBomb => 10,
+                _ => self.get_max_placeable_count(placeable).unwrap_or_default()
+            };
+            pool.push(ItemCount {
+                item: placeable.to_item().unwrap(),
+                count
+            });
+        }
+        let missile_count = item_loc_count.saturating_sub(pool.iter().map(|x| x.count).sum());
+        pool.insert(1, ItemCount {
+            item: Item::Missile,
+            count: missile_count
+        });
+        pool
+    }
+
+    /// Fills empty item locations from `item_pool` using the current logic settings. Items that are already placed are kept
+    /// and count towards the pool. If `area` is set, only locations in rooms of that major area are filled.
+    /// The fill is seeded with `rng_seed`, so the same seed file always gets the same fill. The result has to be applied with `apply_item_fill`
+    pub fn fill_item_locations(&mut self, item_pool: &[ItemCount], area: Option<usize>) -> Result<JoinHandle<Result<Vec<Item>>>> {
+        self.is_map_logic_valid()?;
+
+        self.update_settings();
+
+        let mut remaining_pool = Vec::new();
+        let mut pool_item_count = self.placed_item_count;
+        for item_count in item_pool {
+            let Ok(placeable) = Placeable::from_item(item_count.item) else {
+                continue;
+            };
+            let count = item_count.count.saturating_sub(self.placed_item_count[placeable as usize]);
+            pool_item_count[placeable as usize] += count;
+            remaining_pool.extend(std::iter::repeat_n(item_count.item, count));
+        }
+        if remaining_pool.is_empty() {
+            bail!("All items of the item pool are already placed");
+        }
+
+        let map = self.map();
+        let fill_locations: Vec<bool> = self.game_data.item_locations.iter().enumerate().map(|(i, &(room_id, _))| {
+            let room_idx = self.room_id_to_idx(room_id);
+            self.item_locations[i] == Item::Nothing && map.room_mask[room_idx] && area.is_none_or(|area| map.area[room_idx] == area)
+        }).collect();
+        if !fill_locations.contains(&true) {
+            bail!("There are no empty item locations to fill");
+        }
+
+        let locked_door_data = self.get_locked_door_data();
+        let implicit_tech = &self.preset_data.implicit_tech;
+        let implicit_notables = &self.preset_data.implicit_notables;
+        let difficulty = DifficultyConfig::new(
+            &self.randomizer_settings.skill_assumption_settings,
+            &self.game_data,
+            &implicit_tech,
+            &implicit_notables,
+        );
+
+        let (mut initial_global_state, initial_local_state) = self.get_initial_states(&self.difficulty_tiers[0].tech);
+        initial_global_state.pool_inventory = self.get_pool_inventory(&pool_item_count);
+
+        let handle = self.logic.fill_item_locations(
+            initial_global_state,
+            initial_local_state,
+            self.start_location.clone(),
+            locked_door_data,
+            self.objectives.clone(),
+            difficulty,
+            self.item_locations.clone(),
+            remaining_pool,
+            fill_locations,
+            self.randomizer_settings.clone(),
+            self.difficulty_tiers.clone(),
+            self.map().clone(),
+            self.rng_seed
+        );
+
+        Ok(handle)
+    }
+
+    /// Places the items returned by `fill_item_locations` as a single command. Locations that were filled by the user
+    /// in the meantime are left untouched
+    pub fn apply_item_fill(&mut self, item_locations: Vec<Item>) {
+        self.begin_command(Command::FillItems);
+        for (i, item) in item_locations.into_iter().enumerate() {
+            if self.item_locations[i] == Item::Nothing {
+                self.place_item(i, item);
+            }
+        }
+        self.end_command();
+    }
+
+    /// Logic input for the current seed with the given skill assumptions. All other settings are kept as they are
+    fn get_tier_input(&self, skill_preset: &SkillAssumptionSettings) -> DifficultyTierInput {
+        let implicit_tech = &self.preset_data.implicit_tech;
+        let implicit_notables = &self.preset_data.implicit_notables;
+
+        let mut settings = self.randomizer_settings.clone();
+        settings.skill_assumption_settings = skill_preset.clone();
+
+        let difficulty = DifficultyConfig::new(
+            &settings.skill_assumption_settings,
+            &self.game_data,
+            &implicit_tech,
+            &implicit_notables,
+        );
+        let difficulty_tiers = maprando::randomize::get_difficulty_tiers(
+            &settings,
+            &self.preset_data.difficulty_tiers,
+            &self.game_data,
+            implicit_tech,
+            implicit_notables
+        );
+        let (initial_global_state, initial_local_state) = self.get_initial_states(&difficulty_tiers[0].tech);
+
+        DifficultyTierInput {
+            name: skill_preset.preset.clone().unwrap_or_default(),
+            settings,
+            difficulty,
+            difficulty_tiers,
+            locked_door_data: self.get_locked_door_data(),
+            initial_global_state,
+            initial_local_state
+        }
+    }
+
+    /// Runs the logic once for every skill preset in ascending order until Mother Brain can be defeated.
+    /// All other settings are kept as they are
+    pub fn analyze_min_difficulty(&mut self) -> Result<JoinHandle<Result<DifficultyAnalysis>>> {
+        self.is_map_logic_valid()?;
+
+        self.update_settings();
+        self.update_overrides();
+
+        let tiers = self.preset_data.skill_presets.iter().map(|skill_preset| self.get_tier_input(skill_preset)).collect();
+
+        let handle = tokio::spawn(analysis::analyze_min_difficulty(
+            self.game_data.clone(),
+            tiers,
+            self.start_location.clone(),
+            self.objectives.clone(),
+            self.item_locations.clone(),
+            self.spoiler_overrides.clone(),
+            self.spoiler_flag_overrides.clone(),
+            self.map().clone(),
+            self.custom_escape_time.clone(),
+            self.rng_seed
+        ));
+
+        Ok(handle)
+    }
+
+    /// Finds the placed progression items which are required to beat the seed, by running the logic again with each of their locations emptied.
+    /// Fails if the seed is not beatable with all placed items
+    pub fn analyze_required_items(&mut self) -> Result<JoinHandle<Result<RequiredItemsAnalysis>>> {
+        self.is_map_logic_valid()?;
+
+        self.update_settings();
+        self.update_overrides();
+
+        self.required_items_cancel.cancel();
+        self.required_items_cancel = CancelToken::new();
+        let (progress_tx, progress_rx) = watch::channel(RequiredItemsProgress::default());
+        self.required_items_progress = progress_rx;
+
+        let input = self.get_tier_input(&self.randomizer_settings.skill_assumption_settings);
+        let handle = tokio::spawn(analysis::analyze_required_items(
+            self.game_data.clone(),
+            input,
+            self.start_location.clone(),
+            self.objectives.clone(),
+            self.item_locations.clone(),
+            self.spoiler_overrides.clone(),
+            self.spoiler_flag_overrides.clone(),
+            self.map().clone(),
+            self.custom_escape_time.clone(),
+            self.rng_seed,
+            self.required_items_cancel.clone(),
+            progress_tx
+        ));
+
+        Ok(handle)
+    }
+
+    /// Stops the analysis started by the last call to analyze_required_items. Its handle returns an error
+    pub fn cancel_required_items_analysis(&self) {
+        self.required_items_cancel.cancel();
+    }
+
+    /// Progress of the analysis started by the last call to analyze_required_items
+    pub fn get_required_items_progress(&self) -> RequiredItemsProgress {
+        *self.required_items_progress.borrow()
+    }
+
+    /// Runs the logic with both skill presets at the same time and lists the items whose step, reachability or required tech differs between them.
+    /// All other settings are kept as they are
+    pub fn compare_presets(&mut self, preset_a: &SkillAssumptionSettings, preset_b: &SkillAssumptionSettings) -> Result<JoinHandle<Result<PresetComparison>>> {
+        self.is_map_logic_valid()?;
+
+        self.update_settings();
+        self.update_overrides();
+
+        let presets = [self.get_tier_input(preset_a), self.get_tier_input(preset_b)];
+        let handle = tokio::spawn(analysis::compare_presets(
+            self.game_data.clone(),
+            presets,
+            self.start_location.clone(),
+            self.objectives.clone(),
+            self.item_locations.clone(),
+            self.spoiler_overrides.clone(),
+            self.spoiler_flag_overrides.clone(),
+            self.map().clone(),
+            self.custom_escape_time.clone(),
+            self.rng_seed,
+            self.preset_data.strat_names.clone()
+        ));
+
+        Ok(handle)
+    }
+
+    /// Checks whether `target` can be reached from `start` on the current map and door locks, with the starting items and the given items and flags collected.
+    /// Locked doors on the way have to be opened with that inventory. The result contains the cheapest route if the target is reachable
+    pub fn query_reachability(&mut self, start: (RoomId, NodeId), target: (RoomId, NodeId), items: &[Item], flags: &[usize]) -> Result<JoinHandle<Result<ReachabilityResult>>> {
+        self.is_map_logic_valid()?;
+        self.update_settings();
+
+        let get_vertex_id = |(room_id, node_id): (RoomId, NodeId)| {
+            self.game_data.vertex_isv.index_by_key.get(&VertexKey {
+                room_id,
+                node_id,
+                ..Default::default()
+            }).copied().ok_or(anyhow!("Node {node_id} in room {room_id} does not exist"))
+        };
+        let start_vertex_id = get_vertex_id(start)?;
+        let target_vertex_id = get_vertex_id(target)?;
+
+        let tech = &self.difficulty_tiers[0].tech;
+        let (mut global_state, _) = self.get_initial_states(tech);
+        for &item in items {
+            global_state.collect(item, &self.game_data, self.randomizer_settings.item_progression_settings.ammo_collect_fraction, tech);
+        }
+        for &flag_id in flags {
+            let Some(flag) = global_state.flags.get_mut(flag_id) else {
+                bail!("Flag {flag_id} does not exist");
+            };
+            *flag = true;
+        }
+
+        let implicit_tech = &self.preset_data.implicit_tech;
+        let implicit_notables = &self.preset_data.implicit_notables;
+        let difficulty = DifficultyConfig::new(
+            &self.randomizer_settings.skill_assumption_settings,
+            &self.game_data,
+            &implicit_tech,
+            &implicit_notables,
+        );
+
+        let query = ReachabilityQuery {
+            start_vertex_id,
+            target_vertex_id,
+            global_state,
+            local_state: LocalState::full(false)
+        };
+        let handle = self.logic.query_reachability(
+            query,
+            self.get_locked_door_data(),
+            self.objectives.clone(),
+            difficulty,
+            self.randomizer_settings.clone(),
+            self.difficulty_tiers.clone(),
+            self.map().clone()
+        );
+
+        Ok(handle)
+    }
+
+    /// Items and flags collected in the current spoiler log up to and including `step`, e.g. as the inventory of a reachability query
+    pub fn get_collected_until_step(&self, step: usize) -> (Vec<Item>, Vec<usize>) {
+        let mut items = Vec::new();
+        let mut flags = Vec::new();
+        let lock = self.logic.get_randomization();
+        let Some((_, spoiler_log)) = lock.as_ref() else {
+            return (items, flags);
+        };
+        for details in spoiler_log.details.iter().filter(|details| details.step <= step) {
+            for item_details in &details.items {
+                items.push(ITEM_VALUES[self.game_data.item_isv.index_by_key[&item_details.item]]);
+            }
+            for flag_details in &details.flags {
+                flags.push(self.game_data.flag_isv.index_by_key[&flag_details.flag]);
+            }
+        }
+        (items, flags)
+    }
+
+    /// Tech and notable strats used by the routes of the current spoiler log
+    pub fn get_tech_report(&self) -> Option<TechReport> {
+        let lock = self.logic.get_randomization();
+        let (_, spoiler_log) = lock.as_ref()?;
+        let hub_data = self.logic.get_hub_data();
+        Some(analysis::get_tech_report(&self.game_data, spoiler_log, &hub_data, &self.preset_data.strat_names))
+    }
+
+    /// Dependency graph of the items and flags collected in the current spoiler log
+    pub fn get_progression_graph(&self) -> Option<ProgressionGraph> {
+        let lock = self.logic.get_randomization();
+        let (_, spoiler_log) = lock.as_ref()?;
+        Some(ProgressionGraph::new(&self.game_data, spoiler_log))
+    }
+
+    /// Step by step summary of the current spoiler log, which can be exported as text, Markdown or CSV
+    pub fn get_spoiler_report(&self) -> Option<SpoilerReport> {
+        let lock = self.logic.get_randomization();
+        let (_, spoiler_log) = lock.as_ref()?;
+        Some(SpoilerReport::new(&self.game_data, self.map(), spoiler_log, &self.spoiler_overrides, &self.spoiler_flag_overrides))
+    }
+
     pub fn check_warnings(&mut self) {
-        self.map_editor.error_list.retain(|x| *x != MapErrorType::EscapeNotLogical);
+        self.map_editor.error_list.retain(|x| match x {
+            MapErrorType::EscapeNotLogical | MapErrorType::ItemNotReachable(_) => false,
+            MapErrorType::ItemSoftLock(_, _, _) | MapErrorType::RoomSoftLock(_, _, _) => false,
+            err if err.is_override_warning() => false,
+            _ => true
+        });
+        self.check_item_pool();
 
-        if let Some((r, _)) = self.logic.get_randomization().as_ref() {
-            if r.escape_time_seconds == 5995.0 || r.escape_time_seconds == 0.0 {
+        if let Some((r, s)) = self.logic.get_randomization().as_ref() {
+            if !analysis::is_escape_logical(r) {
                 self.map_editor.error_list.push(MapErrorType::EscapeNotLogical);
             }
+
+            // Every placed item has to be collected in some step, either by logic or by a spoiler override
+            let collected_locations: HashSet<(RoomId, NodeId)> = s.details.iter().flat_map(|details| {
+                details.items.iter().map(|item| (item.location.room_id, item.location.node_id))
+            }).collect();
+            let map = self.map_editor.get_map();
+            let stranded_items: Vec<usize> = (0..self.item_locations.len()).filter(|&idx| {
+                let (room_id, node_id) = self.game_data.item_locations[idx];
+                self.item_locations[idx] != Item::Nothing && map.room_mask[self.room_id_to_idx(room_id)]
+                    && !collected_locations.contains(&(room_id, node_id))
+            }).collect();
+            for idx in stranded_items {
+                self.map_editor.error_list.push(MapErrorType::ItemNotReachable(idx));
+            }
+
+            let override_warnings = analysis::get_override_warnings(&self.game_data, &self.spoiler_overrides, &self.spoiler_flag_overrides, s);
+            self.map_editor.error_list.extend(override_warnings);
+
+            // Items and rooms the player can get to before the logic can get them back out
+            for soft_lock in self.logic.get_soft_locks() {
+                self.map_editor.error_list.push(match soft_lock {
+                    SoftLock::Item(idx, step, return_step) => MapErrorType::ItemSoftLock(idx, step, return_step),
+                    SoftLock::Room(idx, step, return_step) => MapErrorType::RoomSoftLock(idx, step, return_step)
+                });
+            }
         }
     }
 
     /* COPY FROM maprando::randomize::get_initial_states */
-    fn get_initial_states(&self) -> (GlobalState, LocalState) {
+    fn get_initial_states(&self, tech: &[bool]) -> (GlobalState, LocalState) {
         let items = vec![false; self.game_data.item_isv.keys.len()];
         let weapon_mask = self
             .game_data
-            .get_weapon_mask(&items, &self.difficulty_tiers[0].tech);
+            .get_weapon_mask(&items, tech);
         let mut global = GlobalState {
             inventory: Inventory {
                 items: items,
@@ -861,7 +1562,7 @@ impl Plando {
                 collectible_power_bomb_packs: 0,
                 collectible_reserve_tanks: 0,
             },
-            pool_inventory: self.get_pool_inventory(),
+            pool_inventory: self.get_pool_inventory(&self.placed_item_count),
             flags: self.get_initial_flag_vec(),
             doors_unlocked: vec![false; self.locked_doors.len()],
             weapon_mask: weapon_mask,
@@ -875,22 +1576,22 @@ impl Plando {
                     self.randomizer_settings
                         .item_progression_settings
                         .ammo_collect_fraction,
-                    &self.difficulty_tiers[0].tech
+                    tech
                 );
             }
         }
         (global, local)
     }
 
-    fn get_pool_inventory(&self) -> Inventory {
+    fn get_pool_inventory(&self, item_counts: &[usize]) -> Inventory {
         let acf = self.randomizer_settings.item_progression_settings.ammo_collect_fraction;
-        let missile_packs = self.placed_item_count[Item::Missile as usize];
-        let super_packs = self.placed_item_count[Item::Super as usize];
-        let pb_packs = self.placed_item_count[Item::PowerBomb as usize];
-        let etanks = self.placed_item_count[Item::ETank as usize];
-        let reserve_tanks = self.placed_item_count[Item::ReserveTank as usize];
+        let missile_packs = item_counts[Item::Missile as usize];
+        let super_packs = item_counts[Item::Super as usize];
+        let pb_packs = item_counts[Item::PowerBomb as usize];
+        let etanks = item_counts[Item::ETank as usize];
+        let reserve_tanks = item_counts[Item::ReserveTank as usize];
         Inventory {
-            items: self.placed_item_count.iter().map(|&x| x > 0).collect(),
+            items: item_counts.iter().map(|&x| x > 0).collect(),
             max_energy: (99 + etanks * 100) as Capacity,
             max_reserves: (reserve_tanks * 100) as Capacity,
             max_missiles: (acf * missile_packs as f32).round() as Capacity * 5,
@@ -1,13 +1,13 @@
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::{sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex, MutexGuard}, time::{Duration, Instant}};
 
 use anyhow::{bail, Result};
-use maprando::{randomize::{DifficultyConfig, DoorState, FlagLocationState, ItemLocationState, Randomization, RandomizationState, Randomizer, SaveLocationState, StartLocationData, TraverserPair}, settings::{Objective, RandomizerSettings}, spoiler_log::{SpoilerLog, SpoilerRouteEntry, get_spoiler_route}, traverse::{LockedDoorData, Traverser, apply_requirement, get_bireachable_idxs, get_spoiler_trail_ids_by_idx, simple_cost_config}};
+use maprando::{randomize::{DifficultyConfig, DoorState, FlagLocationState, ItemLocationState, Randomization, RandomizationState, Randomizer, SaveLocationState, StartLocationData, TraverserPair}, settings::{Objective, RandomizerSettings}, spoiler_log::{SpoilerLog, SpoilerRouteEntry, get_spoiler_route}, traverse::{LockedDoorData, Traverser, apply_requirement, get_bireachable_idxs, get_one_way_reachable_idx, get_spoiler_trail_ids_by_idx, simple_cost_config}};
 use maprando_game::{Capacity, GameData, HubLocation, Item, ItemLocationId, LinksDataGroup, Map, Requirement, StartLocation, VertexKey};
 use maprando_logic::{GlobalState, LocalState};
-use rand::{rngs::StdRng, RngCore, SeedableRng};
-use tokio::task::JoinHandle;
+use rand::{rngs::StdRng, seq::SliceRandom, RngCore, SeedableRng};
+use tokio::{sync::watch, task::JoinHandle};
 
-use crate::{Plando, SpoilerOverride};
+use crate::{Plando, SpoilerFlagOverride, SpoilerOverride};
 
 pub struct HubLocationData {
     pub hub_location: HubLocation,
@@ -15,17 +15,175 @@ pub struct HubLocationData {
     pub hub_return_route: Vec<SpoilerRouteEntry>
 }
 
+/// Progress of a running spoiler computation, sent after every step
+#[derive(Clone, Copy, Default)]
+pub struct SpoilerProgress {
+    pub step: usize,
+    pub bireachable_items: usize, // Placed items which are bireachable
+    pub bireachable_flags: usize,
+    pub placed_items: usize
+}
+
+/// Stops a running spoiler computation. It is checked once per step, the computation then returns an error without storing a result
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Time spent in the parts of a spoiler computation
+#[derive(Clone, Copy, Default)]
+pub struct LogicTiming {
+    pub links: Duration,
+    pub links_cached: bool, // Filtered links were reused from the previous computation
+    pub setup: Duration, // Randomizer and initial traversal
+    pub traversal: Duration,
+    pub resumed_step: Option<usize>, // Step the traversal was resumed from instead of starting over
+    pub randomization: Duration,
+    pub total: Duration
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct LogicTimings {
+    pub last: Option<LogicTiming>,
+    pub last_full: Option<LogicTiming>, // Last computation which did not use any cached data
+}
+
+/// Traversal of the last spoiler computation. States and traverser step counts are taken before every step,
+/// so the traversal can be resumed from the first step a change can affect
+struct CachedSteps {
+    item_locations: Vec<Item>,
+    spoiler_overrides: Vec<SpoilerOverride>,
+    spoiler_flag_overrides: Vec<SpoilerFlagOverride>,
+    states: Vec<RandomizationState>,
+    traverser_steps: Vec<(usize, usize)>, // Forward and reverse past steps
+    traverser_pair: TraverserPair,
+    start_location_data: StartLocationData,
+    reachability_steps: ReachabilitySteps
+}
+
+/// Data reused between spoiler computations. The Randomizer itself borrows its inputs and is cheap to rebuild compared to the filtered links
+struct LogicCache {
+    links_key: String, // Skill assumptions the links were filtered with
+    links: Arc<LinksDataGroup>,
+    state_key: String, // Everything besides items and overrides the cached steps depend on
+    steps: Option<CachedSteps>
+}
+
+/// Start, target and the explicit state a reachability query is evaluated with. Nothing else is collected or unlocked along the way
+pub struct ReachabilityQuery {
+    pub start_vertex_id: usize,
+    pub target_vertex_id: usize,
+    pub global_state: GlobalState, // Inventory, flags and unlocked doors
+    pub local_state: LocalState // Energy and ammo at the start vertex
+}
+
+#[derive(Clone)]
+pub struct ReachabilityResult {
+    pub reachable: bool,
+    pub route: Vec<SpoilerRouteEntry> // Cheapest route from start to target, empty if the target is not reachable
+}
+
+/// Something the player can get to in an earlier step than they can get back from it
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SoftLock {
+    Item(usize, usize, Option<usize>), // (item_loc, step it is reachable in, step it is bireachable in)
+    Room(usize, usize, Option<usize>) // (room_idx, step it is reachable in, step it is bireachable in)
+}
+
+/// First step in which every item location and room is reachable and bireachable, compared to find soft locks
+#[derive(Clone)]
+struct ReachabilitySteps {
+    items: Vec<(Option<usize>, Option<usize>)>, // (reachable, bireachable) by item location idx
+    rooms: Vec<(Option<usize>, Option<usize>)> // (reachable, bireachable) by room idx. A room counts as bireachable once any of its nodes is
+}
+
+impl ReachabilitySteps {
+    fn new(game_data: &GameData) -> Self {
+        ReachabilitySteps {
+            items: vec![(None, None); game_data.item_locations.len()],
+            rooms: vec![(None, None); game_data.room_geometry.len()]
+        }
+    }
+
+    /// Forgets everything from `step` on, when the traversal is resumed from that step
+    fn truncate(&mut self, step: usize) {
+        for (reachable, bireachable) in self.items.iter_mut().chain(self.rooms.iter_mut()) {
+            if reachable.is_some_and(|x| x >= step) {
+                *reachable = None;
+            }
+            if bireachable.is_some_and(|x| x >= step) {
+                *bireachable = None;
+            }
+        }
+    }
+
+    /// Records what is reachable with the state of the current step. `room_vertices` are the vertex ids of every node without obstacles, by room idx
+    fn update(&mut self, state: &RandomizationState, traverser_pair: &TraverserPair, room_vertices: &[Vec<usize>]) {
+        let step = state.step_num;
+        for ((reachable, bireachable), loc_state) in self.items.iter_mut().zip(&state.item_location_state) {
+            if reachable.is_none() && loc_state.reachable_traversal.is_some() {
+                *reachable = Some(step);
+            }
+            if bireachable.is_none() && loc_state.bireachable_traversal.is_some() {
+                *bireachable = Some(step);
+            }
+        }
+        // Rooms that are already bireachable are done, which leaves fewer rooms to check with every step
+        for ((reachable, bireachable), vertex_ids) in self.rooms.iter_mut().zip(room_vertices) {
+            if bireachable.is_some() {
+                continue;
+            }
+            if reachable.is_none() && vertex_ids.iter().any(|&vertex_id| get_one_way_reachable_idx(vertex_id, &traverser_pair.forward).is_some()) {
+                *reachable = Some(step);
+            }
+            if reachable.is_some() && vertex_ids.iter().any(|&vertex_id| {
+                get_bireachable_idxs(&state.global_state, vertex_id, &traverser_pair.forward, &traverser_pair.reverse).is_some()
+            }) {
+                *bireachable = Some(step);
+            }
+        }
+    }
+
+    /// Placed items and rooms which are reachable before they are bireachable
+    fn get_soft_locks(&self, item_locations: &[Item]) -> Vec<SoftLock> {
+        let is_soft_lock = |&(reachable, bireachable): &(Option<usize>, Option<usize>)| {
+            reachable.is_some_and(|r| bireachable.is_none_or(|b| b > r))
+        };
+        let items = self.items.iter().enumerate().filter(|&(idx, steps)| item_locations[idx] != Item::Nothing && is_soft_lock(steps))
+            .map(|(idx, &(reachable, bireachable))| SoftLock::Item(idx, reachable.unwrap(), bireachable));
+        let rooms = self.rooms.iter().enumerate().filter(|(_, steps)| is_soft_lock(steps))
+            .map(|(idx, &(reachable, bireachable))| SoftLock::Room(idx, reachable.unwrap(), bireachable));
+        items.chain(rooms).collect()
+    }
+}
+
 struct LogicData {
     randomization: Randomization,
     spoiler_log: SpoilerLog,
-    start_location_data: StartLocationData
+    hub_data: HubLocationData,
+    soft_locks: Vec<SoftLock>
 }
 
 pub struct Logic {
 
     game_data: Arc<GameData>,
     randomization: Arc<Mutex<Option<(Randomization, SpoilerLog)>>>,
-    start_location: Arc<Mutex<HubLocationData>>
+    start_location: Arc<Mutex<HubLocationData>>,
+    cache: Arc<Mutex<Option<LogicCache>>>,
+    timings: Arc<Mutex<LogicTimings>>,
+    soft_locks: Arc<Mutex<Vec<SoftLock>>>
 
 }
 
@@ -41,7 +199,10 @@ impl Logic {
                 hub_location: ship_hub,
                 hub_obtain_route: Vec::new(),
                 hub_return_route: Vec::new()
-            }))
+            })),
+            cache: Arc::new(Mutex::new(None)),
+            timings: Arc::new(Mutex::new(LogicTimings::default())),
+            soft_locks: Arc::new(Mutex::new(Vec::new()))
         }
     }
 
@@ -60,6 +221,16 @@ impl Logic {
     pub fn reset(&mut self) {
         let mut lock = self.randomization.lock().unwrap();
         *lock = None;
+        self.soft_locks.lock().unwrap().clear();
+    }
+
+    /// Soft locks found by the last spoiler computation
+    pub fn get_soft_locks(&self) -> Vec<SoftLock> {
+        self.soft_locks.lock().unwrap().clone()
+    }
+
+    pub fn get_timings(&self) -> LogicTimings {
+        *self.timings.lock().unwrap()
     }
 
     pub fn update_hub_and_randomization(
@@ -72,29 +243,64 @@ impl Logic {
         difficulty: DifficultyConfig,
         item_locations: Vec<Item>,
         spoiler_overrides: Vec<SpoilerOverride>,
+        spoiler_flag_overrides: Vec<SpoilerFlagOverride>,
         randomizer_settings: RandomizerSettings,
         difficulty_tiers: Vec<DifficultyConfig>,
         map: Map,
         custom_escape_time: Option<usize>,
-        rebuild_steps: bool
+        rng_seed: u64,
+        rebuild_steps: bool,
+        cancel: CancelToken,
+        progress: watch::Sender<SpoilerProgress>
     ) -> JoinHandle<Result<()>> {
         let arc_hub = self.start_location.clone();
         let arc_r = self.randomization.clone();
+        let arc_cache = self.cache.clone();
+        let arc_timings = self.timings.clone();
+        let arc_soft_locks = self.soft_locks.clone();
         let game_data = self.game_data.clone();
 
         tokio::spawn(async move {
-            let mut rng = StdRng::from_entropy();
+            let total_timer = Instant::now();
+            let mut timing = LogicTiming::default();
+            let mut rng = StdRng::seed_from_u64(rng_seed);
 
             let game_data2 = game_data.clone();
             let game_data3 = game_data.clone();
 
-            let filtered_base_links = maprando::randomize::filter_links(&game_data.links, &game_data, &difficulty);
-            let base_links_data = LinksDataGroup::new(
-                filtered_base_links,
-                game_data.vertex_isv.keys.len(),
-                0,
-            );
-
+            let links_key = serde_json::to_string(&randomizer_settings.skill_assumption_settings)?;
+            let state_key = serde_json::to_string(&(
+                &map,
+                &locked_door_data.locked_doors,
+                &randomizer_settings,
+                &objectives,
+                rng_seed,
+                (start_location.room_id, start_location.node_id, start_location.x, start_location.y)
+            ))?;
+
+            let timer = Instant::now();
+            let cached = arc_cache.lock().unwrap().as_mut().filter(|cache| cache.links_key == links_key).map(|cache| {
+                let steps = if cache.state_key == state_key { cache.steps.take() } else { None };
+                (cache.links.clone(), steps)
+            });
+            let (base_links_data, cached_steps) = match cached {
+                Some(cached) => {
+                    timing.links_cached = true;
+                    cached
+                }
+                None => {
+                    let filtered_base_links = maprando::randomize::filter_links(&game_data.links, &game_data, &difficulty);
+                    let base_links_data = LinksDataGroup::new(
+                        filtered_base_links,
+                        game_data.vertex_isv.keys.len(),
+                        0,
+                    );
+                    (Arc::new(base_links_data), None)
+                }
+            };
+            timing.links = timer.elapsed();
+
+            let timer = Instant::now();
             let randomizer = Randomizer::new(
                 &map,
                 &locked_door_data,
@@ -105,8 +311,9 @@ impl Logic {
                 &base_links_data,
                 &mut rng
             );
+            timing.setup = timer.elapsed();
 
-            let logic_data = update_randomization_impl(
+            let (logic_data, steps) = update_randomization_impl(
                 game_data3,
                 randomizer,
                 initial_global_state,
@@ -114,21 +321,156 @@ impl Logic {
                 start_location,
                 &item_locations,
                 &spoiler_overrides,
+                &spoiler_flag_overrides,
                 custom_escape_time,
-                rebuild_steps
+                rng_seed,
+                rebuild_steps,
+                cached_steps,
+                &mut timing,
+                &cancel,
+                &progress
             ).await?;
 
+            // A newer computation might already have been started
+            if cancel.is_cancelled() {
+                bail!("Spoiler computation was cancelled");
+            }
+            *arc_cache.lock().unwrap() = Some(LogicCache {
+                links_key,
+                links: base_links_data,
+                state_key,
+                steps
+            });
+
             let mut lock = arc_hub.lock().unwrap();
-            lock.hub_location = logic_data.start_location_data.hub_location;
-            lock.hub_obtain_route = logic_data.start_location_data.hub_obtain_route;
-            lock.hub_return_route = logic_data.start_location_data.hub_return_route;
+            *lock = logic_data.hub_data;
             let mut lock = arc_r.lock().unwrap();
             *lock = Some((logic_data.randomization, logic_data.spoiler_log));
+            *arc_soft_locks.lock().unwrap() = logic_data.soft_locks;
+
+            timing.total = total_timer.elapsed();
+            let mut timings = arc_timings.lock().unwrap();
+            timings.last = Some(timing);
+            if !timing.links_cached && timing.resumed_step.is_none() {
+                timings.last_full = Some(timing);
+            }
 
             Ok(())
         })
     }
 
+    /// Checks whether the target vertex of the query can be reached from its start vertex on the given map. Uses the filtered links
+    /// of the last spoiler computation if the skill assumptions match, so repeated queries only have to traverse
+    pub fn query_reachability(
+        &self,
+        query: ReachabilityQuery,
+        locked_door_data: LockedDoorData,
+        objectives: Vec<Objective>,
+        difficulty: DifficultyConfig,
+        randomizer_settings: RandomizerSettings,
+        difficulty_tiers: Vec<DifficultyConfig>,
+        map: Map
+    ) -> JoinHandle<Result<ReachabilityResult>> {
+        let arc_cache = self.cache.clone();
+        let game_data = self.game_data.clone();
+
+        tokio::spawn(async move {
+            // The Randomizer only needs the RNG for parts of the randomization which are not used here
+            let mut rng = StdRng::seed_from_u64(0);
+
+            let links_key = serde_json::to_string(&randomizer_settings.skill_assumption_settings)?;
+            let cached = arc_cache.lock().unwrap().as_ref().filter(|cache| cache.links_key == links_key).map(|cache| cache.links.clone());
+            let base_links_data = match cached {
+                Some(links) => links,
+                None => {
+                    let filtered_base_links = maprando::randomize::filter_links(&game_data.links, &game_data, &difficulty);
+                    let base_links_data = Arc::new(LinksDataGroup::new(
+                        filtered_base_links,
+                        game_data.vertex_isv.keys.len(),
+                        0,
+                    ));
+                    *arc_cache.lock().unwrap() = Some(LogicCache {
+                        links_key,
+                        links: base_links_data.clone(),
+                        state_key: String::new(),
+                        steps: None
+                    });
+                    base_links_data
+                }
+            };
+
+            let randomizer = Randomizer::new(
+                &map,
+                &locked_door_data,
+                objectives.clone(),
+                &randomizer_settings,
+                &difficulty_tiers,
+                &game_data,
+                &base_links_data,
+                &mut rng
+            );
+
+            query_reachability_impl(&game_data, &randomizer, &query)
+        })
+    }
+
+    /// Fills the locations marked in `fill_locations` that are still empty with items from `item_pool`.
+    /// Locations are filled step by step with whatever is bireachable at the current step. Key items are preferably placed
+    /// where an easier difficulty tier can't reach them, leftover filler items go into unreachable locations.
+    /// Fails if a key item could not be placed in a reachable location. Returns the new item locations
+    pub fn fill_item_locations(
+        &self,
+        initial_global_state: GlobalState,
+        initial_local_state: LocalState,
+        start_location: StartLocation,
+        locked_door_data: LockedDoorData,
+        objectives: Vec<Objective>,
+        difficulty: DifficultyConfig,
+        item_locations: Vec<Item>,
+        item_pool: Vec<Item>,
+        fill_locations: Vec<bool>,
+        randomizer_settings: RandomizerSettings,
+        difficulty_tiers: Vec<DifficultyConfig>,
+        map: Map,
+        seed: u64
+    ) -> JoinHandle<Result<Vec<Item>>> {
+        let game_data = self.game_data.clone();
+
+        tokio::spawn(async move {
+            let mut rng = StdRng::seed_from_u64(seed);
+
+            let filtered_base_links = maprando::randomize::filter_links(&game_data.links, &game_data, &difficulty);
+            let base_links_data = LinksDataGroup::new(
+                filtered_base_links,
+                game_data.vertex_isv.keys.len(),
+                0,
+            );
+
+            let randomizer = Randomizer::new(
+                &map,
+                &locked_door_data,
+                objectives.clone(),
+                &randomizer_settings,
+                &difficulty_tiers,
+                &game_data,
+                &base_links_data,
+                &mut rng
+            );
+
+            fill_item_locations_impl(
+                game_data.clone(),
+                &randomizer,
+                initial_global_state,
+                initial_local_state,
+                start_location,
+                item_locations,
+                item_pool,
+                &fill_locations,
+                &mut rng
+            )
+        })
+    }
+
 }
 
 async fn update_randomization_impl(
@@ -139,9 +481,313 @@ async fn update_randomization_impl(
     start_location: StartLocation,
     item_locations: &[Item],
     spoiler_overrides: &[SpoilerOverride],
+    spoiler_flag_overrides: &[SpoilerFlagOverride],
     custom_escape_time: Option<usize>,
-    rebuild_steps: bool
-) -> Result<LogicData> {
+    rng_seed: u64,
+    rebuild_steps: bool,
+    cached_steps: Option<CachedSteps>,
+    timing: &mut LogicTiming,
+    cancel: &CancelToken,
+    progress: &watch::Sender<SpoilerProgress>
+) -> Result<(LogicData, Option<CachedSteps>)> {
+    let timer = Instant::now();
+    let resume = cached_steps.and_then(|steps| {
+        let idx = get_resume_idx(&steps, item_locations, spoiler_overrides, spoiler_flag_overrides)?;
+        Some((steps, idx))
+    });
+    let (mut state, mut traverser_pair, start_location_data, mut states, mut traverser_steps, mut reachability_steps) = match resume {
+        Some((steps, idx)) => {
+            let CachedSteps { mut states, mut traverser_steps, mut traverser_pair, start_location_data, mut reachability_steps, .. } = steps;
+            states.truncate(idx + 1);
+            traverser_steps.truncate(idx + 1);
+            let state = states.pop().unwrap();
+            let (forward_steps, reverse_steps) = traverser_steps.pop().unwrap();
+            while traverser_pair.forward.past_steps.len() > forward_steps {
+                traverser_pair.forward.pop_step();
+            }
+            while traverser_pair.reverse.past_steps.len() > reverse_steps {
+                traverser_pair.reverse.pop_step();
+            }
+            timing.resumed_step = Some(state.step_num);
+            reachability_steps.truncate(state.step_num);
+            (state, traverser_pair, start_location_data, states, traverser_steps, reachability_steps)
+        }
+        None => {
+            let (state, traverser_pair, start_location_data) = init_randomization_state(
+                game_data.clone(),
+                &randomizer,
+                initial_global_state,
+                initial_local_state,
+                start_location
+            )?;
+            (state, traverser_pair, start_location_data, Vec::new(), Vec::new(), ReachabilitySteps::new(&game_data))
+        }
+    };
+
+    for i in 0..state.item_location_state.len() {
+        state.item_location_state[i].placed_item = if item_locations[i] != Item::Nothing {
+            Some(item_locations[i])
+        } else {
+            None
+        };
+    }
+    timing.setup += timer.elapsed();
+
+    let timer = Instant::now();
+    let placed_items = item_locations.iter().filter(|&&item| item != Item::Nothing).count();
+    let max_override_step = spoiler_overrides.iter().map(|x| x.step).chain(spoiler_flag_overrides.iter().map(|x| x.step)).max().unwrap_or_default();
+    let mut room_vertices: Vec<Vec<usize>> = vec![Vec::new(); game_data.room_geometry.len()];
+    for (vertex_id, key) in game_data.vertex_isv.keys.iter().enumerate() {
+        if key.obstacle_mask == 0 && key.actions.is_empty() {
+            room_vertices[game_data.room_idx_by_id[&key.room_id]].push(vertex_id);
+        }
+    }
+
+    loop {
+        if cancel.is_cancelled() {
+            bail!("Spoiler computation was cancelled");
+        }
+
+        reachability_steps.update(&state, &traverser_pair, &room_vertices);
+
+        if !rebuild_steps {
+            states.push(clone_state(&state));
+            traverser_steps.push((traverser_pair.forward.past_steps.len(), traverser_pair.reverse.past_steps.len()));
+        }
+
+        let last_cnt_bireachable = state
+            .item_location_state
+            .iter()
+            .filter(|x| x.bireachable_traversal.is_some())
+            .count();
+        let last_cnt_flag_bireachable = state
+            .flag_location_state
+            .iter()
+            .filter(|x| x.bireachable_traversal.is_some())
+            .count();
+
+        update_step(
+            &mut state,
+            &randomizer,
+            &game_data,
+            randomizer.settings,
+            &randomizer.difficulty_tiers[0].tech,
+            item_locations,
+            spoiler_overrides,
+            spoiler_flag_overrides,
+            &mut traverser_pair
+        );
+
+        let cnt_bireachable = state
+            .item_location_state
+            .iter()
+            .filter(|x| x.bireachable_traversal.is_some())
+            .count();
+        let cnt_flag_bireachable = state
+            .flag_location_state
+            .iter()
+            .filter(|x| x.bireachable_traversal.is_some())
+            .count();
+
+        progress.send_replace(SpoilerProgress {
+            step: state.step_num,
+            bireachable_items: state.item_location_state.iter().filter(|x| x.placed_item.is_some() && x.bireachable_traversal.is_some()).count(),
+            bireachable_flags: cnt_flag_bireachable,
+            placed_items
+        });
+
+        let any_progress = cnt_bireachable > last_cnt_bireachable || cnt_flag_bireachable > last_cnt_flag_bireachable;
+
+        if !any_progress && state.step_num > max_override_step {
+            break;
+        }
+    }
+
+    // Whatever is only reachable after the last step can't be returned from at all
+    reachability_steps.update(&state, &traverser_pair, &room_vertices);
+    let soft_locks = reachability_steps.get_soft_locks(item_locations);
+
+    timing.traversal = timer.elapsed();
+
+    // Steps are cached before get_randomization adds its own traversal steps for the spoiler routes
+    let timer = Instant::now();
+    let forward_steps = traverser_pair.forward.past_steps.len();
+    let reverse_steps = traverser_pair.reverse.past_steps.len();
+
+    for item_loc_state in &mut state.item_location_state {
+        if item_loc_state.placed_item.is_none() {
+            item_loc_state.placed_item = Some(Item::Nothing);
+        }
+    }
+
+    // Separate from the RNG used by the Randomizer, so resuming from cached steps rolls the same seed
+    let mut rng = StdRng::seed_from_u64(rng_seed);
+
+    let seed_part = (rng.next_u32() % 0xFE) + 1; // Generate seed_part 1-255 so seed can't be 0
+    let seed = seed_part | (seed_part << 8) | (seed_part << 16) | (seed_part << 24);
+
+    let (mut r, mut s) = randomizer.get_randomization(
+        &state,
+        seed as usize,
+        seed as usize,
+        &mut rng,
+        &mut traverser_pair,
+        &start_location_data,
+        true,
+        rebuild_steps
+    ).unwrap();
+
+    // Apply custom escape time
+    if let Some(custom_escape_time) = custom_escape_time {
+        let base_igt_seconds = custom_escape_time as f32;
+        let raw_time_seconds = base_igt_seconds; // Ignore multiplier for custom time
+        let final_time_seconds = raw_time_seconds.min(5995.0);
+
+        s.escape.raw_time_seconds = raw_time_seconds;
+        s.escape.final_time_seconds = final_time_seconds;
+
+        r.escape_time_seconds = final_time_seconds;
+    }
+
+    timing.randomization = timer.elapsed();
+
+    let hub_data = HubLocationData {
+        hub_location: start_location_data.hub_location.clone(),
+        hub_obtain_route: start_location_data.hub_obtain_route.clone(),
+        hub_return_route: start_location_data.hub_return_route.clone()
+    };
+
+    let steps_popped = traverser_pair.forward.past_steps.len() < forward_steps || traverser_pair.reverse.past_steps.len() < reverse_steps;
+    let steps = if rebuild_steps || steps_popped {
+        None
+    } else {
+        while traverser_pair.forward.past_steps.len() > forward_steps {
+            traverser_pair.forward.pop_step();
+        }
+        while traverser_pair.reverse.past_steps.len() > reverse_steps {
+            traverser_pair.reverse.pop_step();
+        }
+        Some(CachedSteps {
+            item_locations: item_locations.to_vec(),
+            spoiler_overrides: spoiler_overrides.to_vec(),
+            spoiler_flag_overrides: spoiler_flag_overrides.to_vec(),
+            states,
+            traverser_steps,
+            traverser_pair,
+            start_location_data,
+            reachability_steps
+        })
+    };
+
+    Ok((LogicData {
+        randomization: r,
+        spoiler_log: s,
+        hub_data,
+        soft_locks
+    }, steps))
+}
+
+/// Index of the first cached state a change in item placements or overrides can affect. A changed item only matters
+/// from the step its location becomes bireachable, an override from the step it is applied in
+fn get_resume_idx(
+    steps: &CachedSteps,
+    item_locations: &[Item],
+    spoiler_overrides: &[SpoilerOverride],
+    spoiler_flag_overrides: &[SpoilerFlagOverride]
+) -> Option<usize> {
+    let last_idx = steps.states.len().checked_sub(1)?;
+    if steps.item_locations.len() != item_locations.len() {
+        return None;
+    }
+
+    let mut idx = last_idx;
+    for (loc, (old_item, new_item)) in steps.item_locations.iter().zip(item_locations).enumerate() {
+        if old_item == new_item {
+            continue;
+        }
+        let first_bireachable = steps.states.iter().position(|state| state.item_location_state[loc].bireachable_traversal.is_some());
+        idx = idx.min(first_bireachable.unwrap_or(last_idx));
+        // Overrides on this location now collect a different item
+        for item_override in spoiler_overrides.iter().filter(|x| x.item_idx == loc) {
+            idx = idx.min(item_override.step.saturating_sub(1));
+        }
+    }
+
+    let changed_overrides = spoiler_overrides.iter().filter(|x| !steps.spoiler_overrides.contains(x))
+        .chain(steps.spoiler_overrides.iter().filter(|x| !spoiler_overrides.contains(x)));
+    for item_override in changed_overrides {
+        idx = idx.min(item_override.step.saturating_sub(1));
+    }
+    let changed_flag_overrides = spoiler_flag_overrides.iter().filter(|x| !steps.spoiler_flag_overrides.contains(x))
+        .chain(steps.spoiler_flag_overrides.iter().filter(|x| !spoiler_flag_overrides.contains(x)));
+    for flag_override in changed_flag_overrides {
+        idx = idx.min(flag_override.step.saturating_sub(1));
+    }
+
+    Some(idx)
+}
+
+fn clone_state(state: &RandomizationState) -> RandomizationState {
+    RandomizationState {
+        step_num: state.step_num,
+        start_location: state.start_location.clone(),
+        hub_location: state.hub_location.clone(),
+        item_precedence: state.item_precedence.clone(),
+        item_location_state: state.item_location_state.clone(),
+        flag_location_state: state.flag_location_state.clone(),
+        save_location_state: state.save_location_state.clone(),
+        door_state: state.door_state.clone(),
+        items_remaining: state.items_remaining.clone(),
+        global_state: state.global_state.clone(),
+        starting_local_state: state.starting_local_state,
+        last_key_areas: state.last_key_areas.clone()
+    }
+}
+
+fn query_reachability_impl(game_data: &GameData, randomizer: &Randomizer<'_>, query: &ReachabilityQuery) -> Result<ReachabilityResult> {
+    let global = &query.global_state;
+    let mut traverser = Traverser::new(
+        game_data.vertex_isv.keys.len(),
+        false,
+        query.local_state,
+        global
+    );
+    traverser.add_origin(query.local_state, &global.inventory, query.start_vertex_id);
+    traverser.traverse(
+        randomizer.base_links_data,
+        &randomizer.seed_links_data,
+        global,
+        randomizer.settings,
+        &randomizer.difficulty_tiers[0],
+        game_data,
+        &randomizer.door_map,
+        randomizer.locked_door_data,
+        &randomizer.objectives,
+        0
+    );
+
+    let Some(cost_idx) = get_one_way_reachable_idx(query.target_vertex_id, &traverser) else {
+        return Ok(ReachabilityResult {
+            reachable: false,
+            route: Vec::new()
+        });
+    };
+    let trail_ids = get_spoiler_trail_ids_by_idx(&traverser, query.target_vertex_id, cost_idx);
+    let route = get_spoiler_route(randomizer, global, &trail_ids, &traverser, false);
+
+    Ok(ReachabilityResult {
+        reachable: true,
+        route
+    })
+}
+
+fn init_randomization_state(
+    game_data: Arc<GameData>,
+    randomizer: &Randomizer<'_>,
+    initial_global_state: GlobalState,
+    initial_local_state: LocalState,
+    start_location: StartLocation
+) -> Result<(RandomizationState, TraverserPair, StartLocationData)> {
     let initial_item_location_state = ItemLocationState {
         placed_item: None,
         placed_tier: None,
@@ -182,7 +828,7 @@ async fn update_randomization_impl(
     };
     let (hub, hub_obtain_route, hub_return_route) = update_hub_location_impl(
         game_data.clone(),
-        &randomizer,
+        randomizer,
         initial_global_state.clone(),
         start_location.clone(),
         &mut traverser_pair
@@ -231,13 +877,51 @@ async fn update_randomization_impl(
 
     randomizer.update_reachability(&mut state, &mut traverser_pair);
 
+    Ok((state, traverser_pair, start_location_data))
+}
+
+fn fill_item_locations_impl(
+    game_data: Arc<GameData>,
+    randomizer: &Randomizer<'_>,
+    initial_global_state: GlobalState,
+    initial_local_state: LocalState,
+    start_location: StartLocation,
+    mut item_locations: Vec<Item>,
+    item_pool: Vec<Item>,
+    fill_locations: &[bool],
+    rng: &mut StdRng
+) -> Result<Vec<Item>> {
+    let (mut state, mut traverser_pair, _) = init_randomization_state(
+        game_data.clone(),
+        randomizer,
+        initial_global_state,
+        initial_local_state,
+        start_location
+    )?;
+
     for i in 0..state.item_location_state.len() {
         if item_locations[i] != Item::Nothing {
             state.item_location_state[i].placed_item = Some(item_locations[i]);
         }
     }
 
-    let max_override_step = spoiler_overrides.iter().map(|x| x.step).reduce(|acc, e| acc.max(e)).unwrap_or_default();
+    // Unique items and the first pack of each ammo type can open up new areas, so they are placed one per step.
+    // Everything else is spread over the steps evenly
+    let mut key_items: Vec<Item> = Vec::new();
+    let mut filler_items: Vec<Item> = Vec::new();
+    for item in item_pool {
+        let is_first_ammo = (item == Item::Missile || item == Item::Super || item == Item::PowerBomb)
+            && !item_locations.contains(&item) && !key_items.contains(&item);
+        if item.is_unique() || is_first_ammo {
+            key_items.push(item);
+        } else {
+            filler_items.push(item);
+        }
+    }
+    key_items.shuffle(rng);
+    filler_items.shuffle(rng);
+
+    let is_empty = |i: usize, state: &RandomizationState| fill_locations[i] && state.item_location_state[i].placed_item.is_none();
 
     loop {
         let last_cnt_bireachable = state
@@ -251,14 +935,43 @@ async fn update_randomization_impl(
             .filter(|x| x.bireachable_traversal.is_some())
             .count();
 
+        let cnt_empty = (0..state.item_location_state.len()).filter(|&i| is_empty(i, &state)).count();
+        let mut empty_bireachable: Vec<usize> = (0..state.item_location_state.len()).filter(|&i| {
+            is_empty(i, &state) && state.item_location_state[i].bireachable_traversal.is_some()
+        }).collect();
+        empty_bireachable.shuffle(rng);
+
+        let mut new_items = Vec::new();
+        if !empty_bireachable.is_empty() {
+            if let Some(item) = key_items.pop() {
+                // Key items go where they are the hardest to get with the easier difficulty tiers
+                let hard_idx = find_hard_location(&game_data, randomizer, &state, &empty_bireachable);
+                empty_bireachable.swap(0, hard_idx);
+                new_items.push(item);
+            }
+            let filler_cnt = (filler_items.len() * (empty_bireachable.len() - new_items.len())).div_ceil(cnt_empty);
+            for _ in 0..filler_cnt {
+                let Some(item) = filler_items.pop() else {
+                    break;
+                };
+                new_items.push(item);
+            }
+        }
+        let any_placed = !new_items.is_empty();
+        for (&loc, item) in empty_bireachable.iter().zip(new_items) {
+            state.item_location_state[loc].placed_item = Some(item);
+            item_locations[loc] = item;
+        }
+
         update_step(
             &mut state,
-            &randomizer,
+            randomizer,
             &game_data,
             randomizer.settings,
             &randomizer.difficulty_tiers[0].tech,
-            item_locations,
-            spoiler_overrides,
+            &item_locations,
+            &[],
+            &[],
             &mut traverser_pair
         );
 
@@ -275,50 +988,81 @@ async fn update_randomization_impl(
 
         let any_progress = cnt_bireachable > last_cnt_bireachable || cnt_flag_bireachable > last_cnt_flag_bireachable;
 
-        if !any_progress && state.step_num > max_override_step {
+        if !any_progress && !any_placed {
             break;
         }
     }
 
-    for item_loc_state in &mut state.item_location_state {
-        if item_loc_state.placed_item.is_none() {
-            item_loc_state.placed_item = Some(Item::Nothing);
-        }
+    if !key_items.is_empty() {
+        let names: Vec<String> = key_items.iter().map(|item| format!("{item:?}")).collect();
+        bail!("Not enough reachable locations to place {}", names.join(", "));
     }
 
-    let mut rng = StdRng::from_entropy();
-
-    let seed_part = (rng.next_u32() % 0xFE) + 1; // Generate seed_part 1-255 so seed can't be 0
-    let seed = seed_part | (seed_part << 8) | (seed_part << 16) | (seed_part << 24);
+    // Leftover filler items go into the locations that could not be reached
+    let mut empty: Vec<usize> = (0..state.item_location_state.len()).filter(|&i| is_empty(i, &state)).collect();
+    empty.shuffle(rng);
+    for (loc, item) in empty.into_iter().zip(filler_items) {
+        item_locations[loc] = item;
+    }
 
-    let (mut r, mut s) = randomizer.get_randomization(
-        &state,
-        seed as usize,
-        seed as usize,
-        &mut rng,
-        &mut traverser_pair,
-        &start_location_data,
-        true,
-        rebuild_steps
-    ).unwrap();
+    Ok(item_locations)
+}
 
-    // Apply custom escape time
-    if let Some(custom_escape_time) = custom_escape_time {
-        let base_igt_seconds = custom_escape_time as f32;
-        let raw_time_seconds = base_igt_seconds; // Ignore multiplier for custom time
-        let final_time_seconds = raw_time_seconds.min(5995.0);
+/// Returns the index into `bireachable_locations` of a location that is not bireachable with an easier difficulty tier,
+/// checking the tiers from the hardest to the easiest. Returns 0 if every location is bireachable with all tiers
+fn find_hard_location(
+    game_data: &GameData,
+    randomizer: &Randomizer<'_>,
+    state: &RandomizationState,
+    bireachable_locations: &[ItemLocationId]
+) -> usize {
+    let num_vertices = game_data.vertex_isv.keys.len();
+    let start_vertex_id = game_data.vertex_isv.index_by_key[&VertexKey {
+        room_id: state.hub_location.room_id,
+        node_id: state.hub_location.node_id,
+        obstacle_mask: 0,
+        actions: vec![]
+    }];
 
-        s.escape.raw_time_seconds = raw_time_seconds;
-        s.escape.final_time_seconds = final_time_seconds;
+    for difficulty in randomizer.difficulty_tiers.iter().skip(1) {
+        let global = &state.global_state;
+        let mut forward = Traverser::new(num_vertices, false, state.starting_local_state, global);
+        forward.add_origin(state.starting_local_state, &global.inventory, start_vertex_id);
+        forward.traverse(
+            randomizer.base_links_data,
+            &randomizer.seed_links_data,
+            global,
+            randomizer.settings,
+            difficulty,
+            game_data,
+            &randomizer.door_map,
+            randomizer.locked_door_data,
+            &randomizer.objectives,
+            0
+        );
+        let mut reverse = Traverser::new(num_vertices, true, LocalState::full(true), global);
+        reverse.add_origin(LocalState::full(true), &global.inventory, start_vertex_id);
+        reverse.traverse(
+            randomizer.base_links_data,
+            &randomizer.seed_links_data,
+            global,
+            randomizer.settings,
+            difficulty,
+            game_data,
+            &randomizer.door_map,
+            randomizer.locked_door_data,
+            &randomizer.objectives,
+            0
+        );
 
-        r.escape_time_seconds = final_time_seconds;
+        let hard_idx = bireachable_locations.iter().position(|&loc| {
+            game_data.item_vertex_ids[loc].iter().all(|&v| get_bireachable_idxs(global, v, &forward, &reverse).is_none())
+        });
+        if let Some(idx) = hard_idx {
+            return idx;
+        }
     }
-
-    Ok(LogicData {
-        randomization: r,
-        spoiler_log: s,
-        start_location_data
-    })
+    0
 }
 
 fn update_step(
@@ -329,6 +1073,7 @@ fn update_step(
     tech: &[bool],
     item_locations: &[Item],
     spoiler_overrides: &[SpoilerOverride],
+    spoiler_flag_overrides: &[SpoilerFlagOverride],
     traverser_pair: &mut TraverserPair
 ) {
     loop {
@@ -396,6 +1141,13 @@ fn update_step(
         let item = item_locations[item_override.item_idx];
         new_state.global_state.collect(item, &game_data, randomizer_settings.item_progression_settings.ammo_collect_fraction, tech);
     }
+    let flag_overrides: Vec<_> = spoiler_flag_overrides.iter().filter(|x| x.step == state.step_num).collect();
+    for flag_override in &flag_overrides {
+        // Plando::update_overrides drops unknown flags with a warning, an id out of range is skipped here as well
+        if let Some(flag) = new_state.global_state.flags.get_mut(flag_override.flag_id) {
+            *flag = true;
+        }
+    }
 
     randomizer.update_reachability(&mut new_state, traverser_pair);
 
@@ -416,6 +1168,23 @@ fn update_step(
         state.reachable_traversal = Some(traverse_num);
     }
 
+    // Same for flags, so they are listed in the spoiler at the overridden step
+    for flag_override in flag_overrides {
+        let Some(flag_idx) = game_data.flag_ids.iter().position(|&flag_id| flag_id == flag_override.flag_id) else {
+            continue;
+        };
+        let state = &mut new_state.flag_location_state[flag_idx];
+        if state.bireachable_traversal.is_some() {
+            continue;
+        }
+        let traverse_num = traverser_pair.forward.past_steps.len().saturating_sub(2);
+        let vertex_id = game_data.flag_vertex_ids[flag_idx].first().copied();
+        state.bireachable_traversal = Some(traverse_num);
+        state.bireachable_vertex_id = vertex_id;
+        state.reachable_traversal = Some(traverse_num);
+        state.reachable_vertex_id = vertex_id;
+    }
+
     *state = new_state;
 }
 
@@ -3,9 +3,11 @@ use std::{collections::VecDeque, fs::File, i32, io::{Read, Write}, ops::Sub, pat
 use anyhow::Result;
 use hashbrown::{HashMap, HashSet};
 use maprando::randomize::LockedDoor;
-use maprando_game::{DoorType, GameData, Map};
+use maprando_game::{DoorPtrPair, DoorType, GameData, Map};
 use serde_json::Value;
 
+use crate::{OverrideTarget, Placeable};
+
 #[derive(Clone, Copy)]
 pub struct Rect {
     pub left: i32,
@@ -207,7 +209,24 @@ pub enum MapErrorType {
     EscapeNotLogical,
     AreaNoMap(usize), // area idx which has no map
     AreaSplit(Area),
-    _ItemNotReachable(usize), // item idx which is not reachable
+    ItemNotReachable(usize), // item idx which is not reachable
+    ItemMissing(Placeable, usize, usize), // Fewer items placed than in the target pool (placed, target)
+    ItemSurplus(Placeable, usize, usize), // More items placed than in the target pool (placed, target)
+    ProgressionItemZero(Placeable), // Target pool contains none of a progression item
+    ItemLocationMissing(usize, usize, Placeable), // (room_id, node_id) of an item placed in the seed file, which does not exist in the game data
+    OverrideLocationMissing(usize, usize, usize), // (room_id, node_id, step) of a spoiler override in the seed file, which does not exist in the game data
+    OverrideFlagMissing(usize), // step of a spoiler flag override in the seed file, whose flag does not exist in the game data
+    StartLocationMissing(usize, usize), // (room_id, node_id) of the start location in the seed file, which does not exist in the game data
+    DoorLockMissing(DoorPtrPair), // Door lock in the seed file on a door which does not exist in the game data
+    TechMissing(i32), // tech_id enabled in the seed file which does not exist in the preset data
+    NotableMissing(usize, usize), // (room_id, notable_id) enabled in the seed file which does not exist in the preset data
+    GameDataChanged, // Seed file was made with different game data
+    OverridePastLastStep(OverrideTarget, usize, usize), // Spoiler override (target, step) after the last step logic collects anything in
+    OverrideInLogic(OverrideTarget, usize, usize), // Spoiler override (target, step) on something logic already collects in an earlier step
+    OverrideDuplicate(OverrideTarget, usize), // Second spoiler override (target, step) for the same item location or flag
+    OverrideNoDescription(OverrideTarget, usize), // Spoiler override (target, step) without a description
+    ItemSoftLock(usize, usize, Option<usize>), // Item idx which is reachable in a step (second) before it can be returned from (third, None if never)
+    RoomSoftLock(usize, usize, Option<usize>), // Room idx which is reachable in a step (second) before it can be returned from (third, None if never)
 
     // Errors
     AreaBounds(usize, usize, usize), // Area idx which exceeds boundary limits followed by current (width, height)
@@ -235,7 +254,7 @@ impl MapErrorType {
             MapErrorType::AreaSplit(area) => {
                 format!("{} is split into two or more unconnected Areas or Subareas. Subareas affect room load times when transitioning between them, make sure this is intended.", area.to_string_major())
             }
-            MapErrorType::_ItemNotReachable(idx) => {
+            MapErrorType::ItemNotReachable(idx) => {
                 let (room_id, node_id) = game_data.item_locations[*idx];
                 let room_idx = game_data.room_idx_by_id[&room_id];
                 let room_geometry = &game_data.room_geometry[room_idx];
@@ -243,6 +262,52 @@ impl MapErrorType {
                 let node_name = game_data.node_json_map[&(room_id, node_id)]["name"].as_str().unwrap();
                 format!("Item is not logically reachable. Consider configuring a Spoiler Override. Otherwise the item will not logically appear in the credits. {room_name}: {node_name}")
             }
+            MapErrorType::ItemMissing(placeable, placed, target) =>
+                format!("Fewer {} placed than in the target item pool: Currently {placed}, Target {target}", placeable.to_string()),
+            MapErrorType::ItemSurplus(placeable, placed, target) =>
+                format!("More {} placed than in the target item pool: Currently {placed}, Target {target}", placeable.to_string()),
+            MapErrorType::ProgressionItemZero(placeable) =>
+                format!("Target item pool contains no {}. The seed is most likely not beatable without it", placeable.to_string()),
+            MapErrorType::ItemLocationMissing(room_id, node_id, placeable) =>
+                format!("Seed file places {} at a location which no longer exists (Room {room_id}, Node {node_id}). The item was removed", placeable.to_string()),
+            MapErrorType::OverrideLocationMissing(room_id, node_id, step) =>
+                format!("Seed file has a spoiler override in step {step} for a location which no longer exists (Room {room_id}, Node {node_id}). The override was removed"),
+            MapErrorType::OverrideFlagMissing(step) =>
+                format!("Seed file has a spoiler override in step {step} for a flag which no longer exists. The override was removed"),
+            MapErrorType::StartLocationMissing(room_id, node_id) =>
+                format!("Seed file starts at a location which no longer exists (Room {room_id}, Node {node_id}). The start location was reset to Ship"),
+            MapErrorType::DoorLockMissing(ptr_pair) =>
+                format!("Seed file locks a door which no longer exists ({ptr_pair:?}). The door lock was removed"),
+            MapErrorType::TechMissing(tech_id) =>
+                format!("Seed file enables tech {tech_id}, which no longer exists"),
+            MapErrorType::NotableMissing(room_id, notable_id) =>
+                format!("Seed file enables notable {notable_id} in Room {room_id}, which no longer exists"),
+            MapErrorType::GameDataChanged =>
+                "Seed file was made with different game data. Items, doors and logic might have changed".to_string(),
+            MapErrorType::OverridePastLastStep(target, step, last_step) =>
+                format!("Spoiler override for {} in step {step} is past the last logical step {last_step}. Steps in between will be empty, consider moving it to step {}", target.to_string(game_data), last_step + 1),
+            MapErrorType::OverrideInLogic(target, step, logic_step) =>
+                format!("Spoiler override for {} in step {step} is not needed, it is already in logic in step {logic_step}", target.to_string(game_data)),
+            MapErrorType::OverrideDuplicate(target, step) =>
+                format!("{} has more than one spoiler override. The override in step {step} is applied again", target.to_string(game_data)),
+            MapErrorType::OverrideNoDescription(target, step) =>
+                format!("Spoiler override for {} in step {step} has no description. Consider describing how it is obtained", target.to_string(game_data)),
+            MapErrorType::ItemSoftLock(idx, step, return_step) => {
+                let (room_id, node_id) = game_data.item_locations[*idx];
+                let room_name = &game_data.room_geometry[game_data.room_idx_by_id[&room_id]].name;
+                let node_name = game_data.node_json_map[&(room_id, node_id)]["name"].as_str().unwrap();
+                match return_step {
+                    Some(return_step) => format!("Item can be reached in step {step}, but there is no way back from it until step {return_step}. Picking it up early soft-locks the player. {room_name}: {node_name}"),
+                    None => format!("Item can be reached in step {step}, but there is never a way back from it. Picking it up soft-locks the player. {room_name}: {node_name}")
+                }
+            }
+            MapErrorType::RoomSoftLock(idx, step, return_step) => {
+                let room_name = &game_data.room_geometry[*idx].name;
+                match return_step {
+                    Some(return_step) => format!("{room_name} can be entered in step {step}, but there is no way back out until step {return_step}. Check for one-way drops and doors"),
+                    None => format!("{room_name} can be entered in step {step}, but there is never a way back out. Check for one-way drops and doors")
+                }
+            }
             MapErrorType::AreaBounds(_, w, h) =>
                 format!("Area exceeds maximum size: Currently ({w}, {h}), Maximum: ({}, {})", MapEditor::AREA_MAX_WIDTH, MapEditor::AREA_MAX_HEIGHT),
             MapErrorType::AreaTransitions(t) =>
@@ -276,18 +341,88 @@ impl MapErrorType {
         }
     }
 
+    /// Warnings found while loading a seed file. They are kept until another file is loaded
+    pub fn is_load_warning(&self) -> bool {
+        match self {
+            MapErrorType::ItemLocationMissing(_, _, _) | MapErrorType::OverrideLocationMissing(_, _, _) | MapErrorType::OverrideFlagMissing(_)
+            | MapErrorType::StartLocationMissing(_, _) | MapErrorType::DoorLockMissing(_)
+            | MapErrorType::TechMissing(_) | MapErrorType::NotableMissing(_, _)
+            | MapErrorType::GameDataChanged => true,
+            _ => false
+        }
+    }
+
+    /// Warnings about spoiler overrides. They are recomputed with every spoiler update
+    pub fn is_override_warning(&self) -> bool {
+        match self {
+            MapErrorType::OverridePastLastStep(_, _, _) | MapErrorType::OverrideInLogic(_, _, _)
+            | MapErrorType::OverrideDuplicate(_, _) | MapErrorType::OverrideNoDescription(_, _) => true,
+            _ => false
+        }
+    }
+
+    /// Spoiler step the error refers to
+    pub fn get_step(&self) -> Option<usize> {
+        match self {
+            MapErrorType::OverrideLocationMissing(_, _, step) | MapErrorType::OverrideFlagMissing(step)
+            | MapErrorType::OverridePastLastStep(_, step, _) | MapErrorType::OverrideInLogic(_, step, _)
+            | MapErrorType::OverrideDuplicate(_, step) | MapErrorType::OverrideNoDescription(_, step)
+            | MapErrorType::ItemSoftLock(_, step, _) | MapErrorType::RoomSoftLock(_, step, _) => Some(*step),
+            _ => None
+        }
+    }
+
     pub fn is_severe(&self) -> bool {
         match self {
             MapErrorType::DoorDisconnected(_, _) => false,
             MapErrorType::EscapeNotLogical => false,
             MapErrorType::AreaNoMap(_) => false,
             MapErrorType::AreaSplit(_) => false,
-            MapErrorType::_ItemNotReachable(_) => false,
+            MapErrorType::ItemNotReachable(_) => false,
+            MapErrorType::ItemMissing(_, _, _) => false,
+            MapErrorType::ItemSurplus(_, _, _) => false,
+            MapErrorType::ProgressionItemZero(_) => false,
+            MapErrorType::ItemLocationMissing(_, _, _) => false,
+            MapErrorType::OverrideLocationMissing(_, _, _) => false,
+            MapErrorType::OverrideFlagMissing(_) => false,
+            MapErrorType::StartLocationMissing(_, _) => false,
+            MapErrorType::DoorLockMissing(_) => false,
+            MapErrorType::TechMissing(_) => false,
+            MapErrorType::NotableMissing(_, _) => false,
+            MapErrorType::GameDataChanged => false,
+            MapErrorType::OverridePastLastStep(_, _, _) => false,
+            MapErrorType::OverrideInLogic(_, _, _) => false,
+            MapErrorType::OverrideDuplicate(_, _) => false,
+            MapErrorType::OverrideNoDescription(_, _) => false,
+            MapErrorType::ItemSoftLock(_, _, _) => false,
+            MapErrorType::RoomSoftLock(_, _, _) => false,
             _ => true
         }
     }
 }
 
+#[derive(Clone)]
+pub struct MapEditorSnapshot {
+    map: Map,
+    error_list: Vec<MapErrorType>,
+    invalid_doors: HashSet<(usize, usize)>,
+}
+
+impl MapEditorSnapshot {
+    pub fn is_same_map(&self, other: &MapEditorSnapshot) -> bool {
+        // Door connections may be reordered by snapping a room in place, so compare them unordered
+        let doors: HashSet<_> = self.map.doors.iter().collect();
+        let other_doors: HashSet<_> = other.map.doors.iter().collect();
+
+        self.map.rooms == other.map.rooms
+            && self.map.room_mask == other.map.room_mask
+            && self.map.area == other.map.area
+            && self.map.subarea == other.map.subarea
+            && self.map.subsubarea == other.map.subsubarea
+            && doors == other_doors
+    }
+}
+
 pub struct MapEditor {
     map: Map,
     game_data: Arc<GameData>,
@@ -352,6 +487,20 @@ impl MapEditor {
         &self.map
     }
 
+    pub fn snapshot(&self) -> MapEditorSnapshot {
+        MapEditorSnapshot {
+            map: self.map.clone(),
+            error_list: self.error_list.clone(),
+            invalid_doors: self.invalid_doors.clone()
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: MapEditorSnapshot) {
+        self.map = snapshot.map;
+        self.error_list = snapshot.error_list;
+        self.invalid_doors = snapshot.invalid_doors;
+    }
+
     pub fn move_room(&mut self, room_idx: usize, x: usize, y: usize) {
         self.map.rooms[room_idx] = (x, y);
     }
@@ -421,6 +570,7 @@ impl MapEditor {
     pub fn is_valid(&mut self, locked_doors: &Vec<LockedDoor>) -> bool {
         self.error_list.retain(|err| match err {
             MapErrorType::RoomOverlap(_, _) => true,
+            err if err.is_load_warning() => true,
             _ => false
         });
         self.check_door_connections(locked_doors);
@@ -433,6 +583,14 @@ impl MapEditor {
         self.error_list.is_empty()
     }
 
+    /// Like is_valid, but also checks every room for overlaps instead of only rooms that have been moved
+    pub fn is_valid_full(&mut self, locked_doors: &Vec<LockedDoor>) -> bool {
+        for room_idx in 0..self.map.rooms.len() {
+            self.update_overlaps(room_idx);
+        }
+        self.is_valid(locked_doors)
+    }
+
     pub fn reset(&mut self) {
         self.invalid_doors.clear();
         self.error_list.clear();
@@ -0,0 +1,177 @@
+use anyhow::Result;
+use hashbrown::{HashMap, HashSet};
+use maprando::spoiler_log::{SpoilerLog, SpoilerRouteEntry};
+use maprando_game::{GameData, Requirement};
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::{get_item_location_name, get_strat_requirements};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum ProgressionNodeKind {
+    Item,
+    Flag
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProgressionNode {
+    pub id: String, // Stable between revisions: "item:<room_id>:<node_id>" or "flag:<flag name>"
+    pub kind: ProgressionNodeKind,
+    pub label: String,
+    pub step: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProgressionEdge {
+    pub from: String, // The item or flag whose obtain route has the requirement
+    pub to: String, // The required item or flag
+}
+
+/// Directed graph of the spoiler log. Every collected item and flag is a node, with edges to the items and flags its obtain route needs.
+/// Required items point to the first pickup of that item in an earlier step
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ProgressionGraph {
+    pub nodes: Vec<ProgressionNode>,
+    pub edges: Vec<ProgressionEdge>,
+}
+
+/// Whether the items and flags of a requirement could have been collected. Everything else is assumed to be possible
+fn is_requirement_possible(req: &Requirement, is_collected: &impl Fn(&Requirement) -> bool) -> bool {
+    match req {
+        Requirement::Item(_) | Requirement::Flag(_) => is_collected(req),
+        Requirement::And(reqs) => reqs.iter().all(|req| is_requirement_possible(req, is_collected)),
+        Requirement::Or(reqs) => reqs.iter().any(|req| is_requirement_possible(req, is_collected)),
+        _ => true
+    }
+}
+
+/// Collects the items and flags of a requirement. Alternatives of an Or requirement that need something which was not collected yet
+/// can't have been taken and are skipped. If several alternatives remain possible, all of them are collected
+fn collect_requirement_progression(req: &Requirement, is_collected: &impl Fn(&Requirement) -> bool, items: &mut HashSet<usize>, flags: &mut HashSet<usize>) {
+    match req {
+        Requirement::Item(item_idx) => { items.insert(*item_idx); }
+        Requirement::Flag(flag_idx) => { flags.insert(*flag_idx); }
+        Requirement::And(reqs) => {
+            for req in reqs {
+                collect_requirement_progression(req, is_collected, items, flags);
+            }
+        }
+        Requirement::Or(reqs) => {
+            for req in reqs.iter().filter(|req| is_requirement_possible(req, is_collected)) {
+                collect_requirement_progression(req, is_collected, items, flags);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl ProgressionGraph {
+    pub fn new(game_data: &GameData, spoiler_log: &SpoilerLog) -> Self {
+        let strat_reqs = get_strat_requirements(game_data);
+        let item_loc_map: HashMap<(usize, usize), usize> = game_data.item_locations.iter().enumerate().map(|(idx, &loc)| (loc, idx)).collect();
+
+        let mut graph = ProgressionGraph::default();
+        // First pickup of each item and each flag, with the step they were collected in
+        let mut first_item: HashMap<String, (String, usize)> = HashMap::new();
+        let mut flag_nodes: HashMap<String, (String, usize)> = HashMap::new();
+        let mut routes: Vec<(String, usize, &Vec<SpoilerRouteEntry>)> = Vec::new();
+
+        for details in &spoiler_log.details {
+            for item in &details.items {
+                let loc = (item.location.room_id, item.location.node_id);
+                let id = format!("item:{}:{}", loc.0, loc.1);
+                let location_name = match item_loc_map.get(&loc) {
+                    Some(&idx) => get_item_location_name(game_data, idx),
+                    None => format!("{}: {}", item.location.room, item.location.node)
+                };
+                graph.nodes.push(ProgressionNode {
+                    id: id.clone(),
+                    kind: ProgressionNodeKind::Item,
+                    label: format!("{} ({location_name})", item.item),
+                    step: details.step
+                });
+                first_item.entry(item.item.clone()).or_insert((id.clone(), details.step));
+                routes.push((id, details.step, &item.obtain_route));
+            }
+            for flag in &details.flags {
+                let id = format!("flag:{}", flag.flag);
+                graph.nodes.push(ProgressionNode {
+                    id: id.clone(),
+                    kind: ProgressionNodeKind::Flag,
+                    label: flag.flag.clone(),
+                    step: details.step
+                });
+                flag_nodes.insert(flag.flag.clone(), (id.clone(), details.step));
+                routes.push((id, details.step, &flag.obtain_route));
+            }
+        }
+
+        let mut edges = HashSet::new();
+        for (id, step, route) in routes {
+            // Items and flags that never show up in the spoiler log could be collected from the start
+            let is_collected = |req: &Requirement| match req {
+                Requirement::Item(item_idx) => first_item.get(&game_data.item_isv.keys[*item_idx]).is_none_or(|x| x.1 < step),
+                Requirement::Flag(flag_idx) => flag_nodes.get(&game_data.flag_isv.keys[*flag_idx]).is_none_or(|x| x.1 < step),
+                _ => true
+            };
+            let mut items = HashSet::new();
+            let mut flags = HashSet::new();
+            for entry in route {
+                if let Some(req) = entry.strat_id.and_then(|strat_id| strat_reqs.get(&(entry.room_id, strat_id))) {
+                    collect_requirement_progression(req, &is_collected, &mut items, &mut flags);
+                }
+            }
+
+            // Only items and flags collected in an earlier step can be required
+            let item_targets = items.into_iter().filter_map(|item_idx| first_item.get(&game_data.item_isv.keys[item_idx]));
+            let flag_targets = flags.into_iter().filter_map(|flag_idx| flag_nodes.get(&game_data.flag_isv.keys[flag_idx]));
+            for (target_id, target_step) in item_targets.chain(flag_targets) {
+                if *target_step < step {
+                    edges.insert(ProgressionEdge {
+                        from: id.clone(),
+                        to: target_id.clone()
+                    });
+                }
+            }
+        }
+        graph.edges = edges.into_iter().collect();
+        graph.edges.sort();
+
+        graph
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut lines = vec!["digraph progression {".to_string(), "    rankdir=LR;".to_string()];
+
+        let max_step = self.nodes.iter().map(|node| node.step).max().unwrap_or_default();
+        for step in 1..=max_step {
+            let step_nodes: Vec<&ProgressionNode> = self.nodes.iter().filter(|node| node.step == step).collect();
+            if step_nodes.is_empty() {
+                continue;
+            }
+            lines.push(format!("    subgraph cluster_step_{step} {{"));
+            lines.push(format!("        label=\"Step {step}\";"));
+            for node in step_nodes {
+                let shape = match node.kind {
+                    ProgressionNodeKind::Item => "box",
+                    ProgressionNodeKind::Flag => "ellipse"
+                };
+                lines.push(format!("        \"{}\" [label=\"{}\", shape={shape}];", escape_dot(&node.id), escape_dot(&node.label)));
+            }
+            lines.push("    }".to_string());
+        }
+        for edge in &self.edges {
+            lines.push(format!("    \"{}\" -> \"{}\";", escape_dot(&edge.from), escape_dot(&edge.to)));
+        }
+        lines.push("}".to_string());
+
+        lines.join("\n")
+    }
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
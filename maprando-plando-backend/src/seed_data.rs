@@ -1,13 +1,60 @@
-use std::{io::Write, path::Path};
+use std::{collections::BTreeMap, io::Write, path::Path};
 
 use anyhow::{anyhow, bail, Result};
-use hashbrown::HashSet;
-use maprando::{preset::PresetData, randomize::LockedDoor, settings::{try_upgrade_settings, NotableSetting, Objective, ObjectiveSetting, RandomizerSettings, TechSetting}};
-use maprando_game::{BeamType, DoorType, GameData, Item, Map};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hashbrown::{HashMap, HashSet};
+use maprando::{preset::PresetData, randomize::LockedDoor, settings::{try_upgrade_settings, ItemCount, NotableSetting, Objective, ObjectiveSetting, RandomizerSettings, TechSetting}};
+use maprando_game::{BeamType, DoorPtrPair, DoorType, GameData, Item, Map, NodeId, RoomId};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-use crate::{Placeable, Plando, SpoilerOverride};
+use crate::{analysis::get_item_location_name, map_editor::{Area, MapErrorType}, Placeable, Plando, SpoilerFlagOverride, SpoilerOverride};
+
+/// A single semantic difference between two SeedData
+#[derive(Clone)]
+pub enum SeedDiffEntry {
+    RoomAdded(usize), // room_idx
+    RoomRemoved(usize),
+    RoomMoved(usize, (usize, usize), (usize, usize)),
+    AreaChanged(usize, Area, Area),
+    DoorLockAdded(DoorPtrPair, DoorType),
+    DoorLockRemoved(DoorPtrPair, DoorType),
+    DoorLockChanged(DoorPtrPair, DoorType, DoorType),
+    ItemChanged(usize, Item, Item), // item_loc
+    StartLocationChanged((RoomId, NodeId), (RoomId, NodeId)),
+    SettingChanged(String, String, String), // path, old value, new value
+}
+
+impl SeedDiffEntry {
+    pub fn to_string(&self, game_data: &GameData) -> String {
+        match self {
+            SeedDiffEntry::RoomAdded(room_idx) => format!("Room added: {}", game_data.room_geometry[*room_idx].name),
+            SeedDiffEntry::RoomRemoved(room_idx) => format!("Room removed: {}", game_data.room_geometry[*room_idx].name),
+            SeedDiffEntry::RoomMoved(room_idx, old, new) => {
+                format!("Room moved: {} from ({}, {}) to ({}, {})", game_data.room_geometry[*room_idx].name, old.0, old.1, new.0, new.1)
+            }
+            SeedDiffEntry::AreaChanged(room_idx, old, new) => {
+                format!("Area changed: {} from {} to {}", game_data.room_geometry[*room_idx].name, old.to_string(), new.to_string())
+            }
+            SeedDiffEntry::DoorLockAdded(ptr_pair, door_type) => {
+                format!("Door lock added: {} at {}", get_door_type_name(*door_type), get_door_name(game_data, *ptr_pair))
+            }
+            SeedDiffEntry::DoorLockRemoved(ptr_pair, door_type) => {
+                format!("Door lock removed: {} at {}", get_door_type_name(*door_type), get_door_name(game_data, *ptr_pair))
+            }
+            SeedDiffEntry::DoorLockChanged(ptr_pair, old, new) => {
+                format!("Door lock changed: {} from {} to {}", get_door_name(game_data, *ptr_pair), get_door_type_name(*old), get_door_type_name(*new))
+            }
+            SeedDiffEntry::ItemChanged(item_loc, old, new) => {
+                format!("Item changed: {} from {} to {}", get_item_location_name(game_data, *item_loc), get_item_name(*old), get_item_name(*new))
+            }
+            SeedDiffEntry::StartLocationChanged(old, new) => {
+                format!("Start location changed from {} to {}", get_start_location_name(game_data, *old), get_start_location_name(game_data, *new))
+            }
+            SeedDiffEntry::SettingChanged(path, old, new) => format!("Setting changed: {path} from {old} to {new}")
+        }
+    }
+}
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SeedData {
@@ -18,14 +65,64 @@ pub struct SeedData {
     pub settings: RandomizerSettings,
     #[serde(default = "Vec::new")]
     pub spoiler_overrides: Vec<SpoilerOverride>,
+    #[serde(default = "Vec::new")]
+    pub spoiler_flag_overrides: Vec<SpoilerFlagOverride>,
     #[serde(default)]
     pub custom_escape_time: Option<usize>,
     #[serde(default)]
-    pub creator_name: String
+    pub creator_name: String,
+    #[serde(default)]
+    pub item_pool: Option<Vec<ItemCount>>,
+    #[serde(default)]
+    pub objectives: Option<Vec<Objective>>, // Objectives picked by the author, rolled from the settings if None
+    #[serde(default)]
+    pub rng_seed: Option<u64>, // Seed of the backend RNG. Files saved before it was stored get a random one when loaded
+    #[serde(default)]
+    pub game_data_fingerprint: Option<String>, // Game data the seed was made with, see get_game_data_fingerprint
+    #[serde(default)]
+    pub maprando_commit: Option<String>, // MapRandomizer commit the seed was made with
+    #[serde(skip)]
+    pub load_warnings: Vec<MapErrorType> // Problems found while loading the file, shown as warnings after loading it into a Plando
+}
+
+/// Item placement as stored in the file, keyed by location instead of the index into game_data.item_locations
+#[derive(Serialize, Deserialize)]
+struct ItemPlacementEntry {
+    room_id: RoomId,
+    node_id: NodeId,
+    item: Item
+}
+
+/// Spoiler override as stored in the file, keyed by location instead of the index into game_data.item_locations
+#[derive(Serialize, Deserialize)]
+struct SpoilerOverrideEntry {
+    step: usize,
+    room_id: RoomId,
+    node_id: NodeId,
+    description: String
+}
+
+/// Spoiler flag override as stored in the file, keyed by flag name instead of the flag id
+#[derive(Serialize, Deserialize)]
+struct SpoilerFlagOverrideEntry {
+    step: usize,
+    flag: String,
+    description: String
 }
 
 impl SeedData {
-    pub const FORMAT: &'static str = "0.2.2";
+    /// Format written to seed files:
+    /// - 0.2.1 and before: Full settings, start location as index
+    /// - 0.2.2: Compacted settings, room_mask and door_locks are compacted in newer files only. Format was not written to the file
+    /// - 0.3.0: Format is written to the file. Only written by development builds, it was never part of a release
+    /// - 0.4.0: Item placements and spoiler overrides are keyed by (room_id, node_id) instead of the item location index
+    pub const FORMAT: &'static str = "0.4.0";
+    /// Version byte in front of the compressed data of a share code
+    pub const SHARE_CODE_VERSION: u8 = 1;
+    /// Upper limit of the decompressed JSON of a share code. Seed files are far smaller, anything above is rejected before it is allocated
+    pub const MAX_SEED_JSON_BYTES: usize = 4 * 1024 * 1024;
+    /// MapRandomizer commit the backend was built against, recorded by build.rs
+    pub const MAPRANDO_COMMIT: &'static str = env!("MAPRANDO_COMMIT");
 
     pub fn from_plando(plando: &Plando) -> Self {
         Self {
@@ -35,8 +132,15 @@ impl SeedData {
             door_locks: plando.locked_doors.clone(),
             settings: plando.randomizer_settings.clone(),
             spoiler_overrides: plando.spoiler_overrides.clone(),
+            spoiler_flag_overrides: plando.spoiler_flag_overrides.clone(),
             custom_escape_time: plando.custom_escape_time.clone(),
-            creator_name: plando.creator_name.clone()
+            creator_name: plando.creator_name.clone(),
+            item_pool: plando.item_pool.clone(),
+            objectives: plando.custom_objectives.clone(),
+            rng_seed: Some(plando.rng_seed),
+            game_data_fingerprint: Some(get_game_data_fingerprint(&plando.game_data)),
+            maprando_commit: Some(Self::MAPRANDO_COMMIT.to_string()),
+            load_warnings: Vec::new()
         }
     }
 
@@ -52,13 +156,30 @@ impl SeedData {
         Self::from_json(v, game_data, preset_data)
     }
 
+    /// Decodes a seed from a share code created by to_share_code
+    pub fn from_share_code(code: &str, game_data: &GameData, preset_data: &PresetData) -> Result<SeedData> {
+        let data = URL_SAFE_NO_PAD.decode(code.trim()).map_err(|err| anyhow!("Invalid seed code: {err}"))?;
+        let (&version, compressed) = data.split_first().ok_or(anyhow!("Seed code is empty"))?;
+        if version > Self::SHARE_CODE_VERSION {
+            bail!("Seed code was made with a newer plando version");
+        }
+        let json = zstd::bulk::decompress(compressed, Self::MAX_SEED_JSON_BYTES).map_err(|err| anyhow!("Invalid seed code: {err}"))?;
+        let v: Value = serde_json::from_slice(&json)?;
+        Self::from_json(v, game_data, preset_data)
+    }
+
     pub fn load_into_plando(self, plando: &mut Plando) -> Result<()> {
         plando.load_preset(self.settings);
 
         plando.custom_escape_time = self.custom_escape_time;
         plando.creator_name = self.creator_name;
+        plando.item_pool = self.item_pool;
 
         plando.load_map(self.map);
+        if let Some(rng_seed) = self.rng_seed {
+            plando.set_rng_seed(rng_seed);
+        }
+        plando.set_objectives(self.objectives);
 
         plando.item_locations = self.item_placements;
         for item in &plando.item_locations {
@@ -84,46 +205,201 @@ impl SeedData {
         plando.place_start_location(start_loc);
 
         plando.spoiler_overrides = self.spoiler_overrides;
+        plando.spoiler_flag_overrides = self.spoiler_flag_overrides;
+        plando.history.clear();
+        plando.check_item_pool();
+        plando.map_editor.error_list.extend(self.load_warnings);
 
         Ok(())
     }
 
+    /// Reads the format of a seed file. Files before 0.3.0 did not store their format, it is derived from their layout instead
+    pub fn detect_format(v: &Value) -> Result<String> {
+        if let Some(format) = v.get("format") {
+            return format.as_str().map(|s| s.to_string()).ok_or(anyhow!("Expected key \"format\" to be of type string"));
+        }
+
+        // <= 0.2.1 stored the start location as an index and the full tech settings
+        let start_location_idx = v.get("start_location").is_some_and(|x| x.is_number());
+        let full_tech_settings = v.pointer("/settings/skill_assumption_settings/tech_settings/0").is_some_and(|x| x.is_object());
+        if start_location_idx || full_tech_settings {
+            Ok("0.2.1".to_string())
+        } else {
+            Ok("0.2.2".to_string())
+        }
+    }
+
     fn from_json(mut v: Value, game_data: &GameData, preset_data: &PresetData) -> Result<SeedData> {
-        // Try parsing an older version (<= v0.2.1)
-        if let Ok(r) = Self::from_json_legacy(v.clone(), game_data, preset_data) {
-            return Ok(r);
+        let format = Self::detect_format(&v)?;
+        if parse_format(&format)? > parse_format(Self::FORMAT)? {
+            bail!("This seed was made with a newer plando version (format {format}, supported up to {}). Please update maprando-plando", Self::FORMAT);
         }
+        let Some(first_migration) = MIGRATIONS.iter().position(|(version, _)| *version == format) else {
+            if format != Self::FORMAT {
+                bail!("Unknown seed format {format}");
+            }
+            let mut seed_data = Self::from_json_current(v, game_data, preset_data)?;
+            seed_data.check_game_data(game_data)?;
+            return Ok(seed_data);
+        };
 
-        // Uncompact room_mask from Bitmask to a Vec<bool>
+        for (_, migrate) in &MIGRATIONS[first_migration..] {
+            v = migrate(v, game_data, preset_data)?;
+        }
+        let mut seed_data = Self::from_json_current(v, game_data, preset_data)?;
+        seed_data.check_game_data(game_data)?;
+        Ok(seed_data)
+    }
+
+    /// Warns about a seed made with different game data and removes references to rooms and doors that no longer exist, which could not be loaded otherwise.
+    /// Fails if the number of rooms changed, as the map can't be matched to the game data then
+    fn check_game_data(&mut self, game_data: &GameData) -> Result<()> {
+        if self.map.rooms.len() != game_data.room_geometry.len() {
+            bail!(
+                "Seed file has {} rooms, but the game data has {}. The map will most likely not load correctly",
+                self.map.rooms.len(), game_data.room_geometry.len()
+            );
+        }
+
+        if let Some(fingerprint) = &self.game_data_fingerprint {
+            if *fingerprint != get_game_data_fingerprint(game_data) {
+                self.load_warnings.push(MapErrorType::GameDataChanged);
+            }
+        }
+
+        let ship_start = Plando::get_ship_start();
+        let ship_start_loc = (ship_start.room_id, ship_start.node_id);
+        if self.start_location != ship_start_loc && !game_data.start_location_id_map.contains_key(&self.start_location) {
+            self.load_warnings.push(MapErrorType::StartLocationMissing(self.start_location.0, self.start_location.1));
+            self.start_location = ship_start_loc;
+        }
+
+        let load_warnings = &mut self.load_warnings;
+        self.door_locks.retain(|lock| {
+            let exists = game_data.room_and_door_idxs_by_door_ptr_pair.contains_key(&lock.src_ptr_pair);
+            if !exists {
+                load_warnings.push(MapErrorType::DoorLockMissing(lock.src_ptr_pair));
+            }
+            exists
+        });
+        Ok(())
+    }
+
+    /// 0.2.1 -> 0.2.2: Parses the legacy file and compacts it again, which is also a valid 0.2.2 file
+    fn migrate_0_2_1(v: Value, game_data: &GameData, preset_data: &PresetData) -> Result<Value> {
+        let seed_data = Self::from_json_legacy(v, game_data, preset_data)?;
+        let mut v = seed_data.to_json_compact()?;
+        as_obj(&mut v)?.insert("format".to_string(), Value::String("0.2.2".to_string()));
+        Ok(v)
+    }
+
+    /// 0.2.2 -> 0.3.0: room_mask and door_locks were not compacted in files made for v119
+    fn migrate_0_2_2(mut v: Value, _game_data: &GameData, _preset_data: &PresetData) -> Result<Value> {
         let map = get_key(&mut v, "map")?;
+        let room_count = as_arr(get_key(map, "rooms")?)?.len();
         let room_mask_v = get_key(map, "room_mask")?;
-        // v119: room_mask is not compressed
-        if as_arr(room_mask_v)?.len() < game_data.room_geometry.len() {
-            let room_mask: Vec<u8> = serde_json::from_value(room_mask_v.take())?;
-            let mut room_mask_out = Vec::with_capacity(room_mask.len() * 8);
-            for val in room_mask {
-                for offset in 0..8 {
-                    let b = val & (1 << offset);
-                    if b > 0 {
-                        room_mask_out.push(true);
-                    } else {
-                        room_mask_out.push(false);
-                    }
+        if as_arr(room_mask_v)?.len() >= room_count {
+            let room_mask: Vec<bool> = serde_json::from_value(room_mask_v.take())?;
+            *room_mask_v = serde_json::to_value(compact_room_mask(&room_mask))?;
+        }
+
+        let door_locks = get_key(&mut v, "door_locks")?;
+        for door_lock in as_arr(door_locks)? {
+            let door_lock_obj = as_obj(door_lock)?;
+            door_lock_obj.remove("dst_ptr_pair");
+            door_lock_obj.remove("bidirectional");
+        }
+
+        as_obj(&mut v)?.insert("format".to_string(), Value::String("0.3.0".to_string()));
+        Ok(v)
+    }
+
+    /// 0.3.0 -> 0.4.0: Item placements and spoiler overrides were stored by item location index.
+    /// The indices are resolved with the current game data, as the file does not know which game data it was made with
+    fn migrate_0_3_0(mut v: Value, game_data: &GameData, _preset_data: &PresetData) -> Result<Value> {
+        let item_placements_v = get_key(&mut v, "item_placements")?;
+        let item_placements: Vec<Item> = serde_json::from_value(item_placements_v.take())?;
+        let entries: Vec<ItemPlacementEntry> = item_placements.into_iter().zip(game_data.item_locations.iter()).filter_map(|(item, &(room_id, node_id))| {
+            if item == Item::Nothing { None } else { Some(ItemPlacementEntry { room_id, node_id, item }) }
+        }).collect();
+        *item_placements_v = serde_json::to_value(entries)?;
+
+        if let Some(spoiler_overrides_v) = v.get_mut("spoiler_overrides") {
+            let spoiler_overrides: Vec<SpoilerOverride> = serde_json::from_value(spoiler_overrides_v.take())?;
+            let entries: Vec<SpoilerOverrideEntry> = spoiler_overrides.into_iter().filter_map(|x| {
+                let &(room_id, node_id) = game_data.item_locations.get(x.item_idx)?;
+                Some(SpoilerOverrideEntry { step: x.step, room_id, node_id, description: x.description })
+            }).collect();
+            *spoiler_overrides_v = serde_json::to_value(entries)?;
+        }
+
+        as_obj(&mut v)?.insert("format".to_string(), Value::String("0.4.0".to_string()));
+        Ok(v)
+    }
+
+    fn from_json_current(mut v: Value, game_data: &GameData, preset_data: &PresetData) -> Result<SeedData> {
+        as_obj(&mut v)?.remove("format");
+        let mut load_warnings = Vec::new();
+
+        // Resolve item placements and spoiler overrides to item location indices, flagging locations that no longer exist
+        let item_loc_map: HashMap<(RoomId, NodeId), usize> = game_data.item_locations.iter().enumerate().map(|(idx, &loc)| (loc, idx)).collect();
+        let item_placements_v = get_key(&mut v, "item_placements")?;
+        let entries: Vec<ItemPlacementEntry> = serde_json::from_value(item_placements_v.take())?;
+        let mut item_placements = vec![Item::Nothing; game_data.item_locations.len()];
+        for entry in entries {
+            match item_loc_map.get(&(entry.room_id, entry.node_id)) {
+                Some(&idx) => item_placements[idx] = entry.item,
+                None => if let Ok(placeable) = Placeable::from_item(entry.item) {
+                    load_warnings.push(MapErrorType::ItemLocationMissing(entry.room_id, entry.node_id, placeable));
+                }
+            }
+        }
+        *item_placements_v = serde_json::to_value(item_placements)?;
+
+        if let Some(spoiler_overrides_v) = v.get_mut("spoiler_overrides") {
+            let entries: Vec<SpoilerOverrideEntry> = serde_json::from_value(spoiler_overrides_v.take())?;
+            let mut spoiler_overrides = Vec::new();
+            for entry in entries {
+                match item_loc_map.get(&(entry.room_id, entry.node_id)) {
+                    Some(&item_idx) => spoiler_overrides.push(SpoilerOverride { step: entry.step, item_idx, description: entry.description }),
+                    None => load_warnings.push(MapErrorType::OverrideLocationMissing(entry.room_id, entry.node_id, entry.step))
+                }
+            }
+            *spoiler_overrides_v = serde_json::to_value(spoiler_overrides)?;
+        }
+
+        if let Some(flag_overrides_v) = v.get_mut("spoiler_flag_overrides") {
+            let entries: Vec<SpoilerFlagOverrideEntry> = serde_json::from_value(flag_overrides_v.take())?;
+            let mut flag_overrides = Vec::new();
+            for entry in entries {
+                match game_data.flag_isv.index_by_key.get(&entry.flag) {
+                    Some(&flag_id) => flag_overrides.push(SpoilerFlagOverride { step: entry.step, flag_id, description: entry.description }),
+                    None => load_warnings.push(MapErrorType::OverrideFlagMissing(entry.step))
                 }
             }
-            *room_mask_v = serde_json::to_value(room_mask_out)?;
+            *flag_overrides_v = serde_json::to_value(flag_overrides)?;
         }
 
+        // Uncompact room_mask from Bitmask to a Vec<bool>
+        let map = get_key(&mut v, "map")?;
+        let room_count = as_arr(get_key(map, "rooms")?)?.len();
+        let room_mask_v = get_key(map, "room_mask")?;
+        let room_mask: Vec<u8> = serde_json::from_value(room_mask_v.take())?;
+        let mut room_mask_out = Vec::with_capacity(room_mask.len() * 8);
+        for val in room_mask {
+            for offset in 0..8 {
+                room_mask_out.push(val & (1 << offset) > 0);
+            }
+        }
+        room_mask_out.truncate(room_count);
+        *room_mask_v = serde_json::to_value(room_mask_out)?;
+
         // Insert omitted fields into door_locks
         let door_locks = get_key(&mut v, "door_locks")?;
-        let door_locks_arr = as_arr(door_locks)?;
-        // v119: door_locks are not compressed
-        if door_locks_arr.first().map(|lock| serde_json::from_value::<LockedDoor>(lock.clone()).is_err()).unwrap_or(false) {
-            for door_lock in door_locks_arr {
-                let door_lock_obj = as_obj(door_lock)?;
-                door_lock_obj.insert("dst_ptr_pair".to_string(), json!([null, null]));
-                door_lock_obj.insert("bidirectional".to_string(), Value::Bool(false));
-            }
+        for door_lock in as_arr(door_locks)? {
+            let door_lock_obj = as_obj(door_lock)?;
+            door_lock_obj.insert("dst_ptr_pair".to_string(), json!([null, null]));
+            door_lock_obj.insert("bidirectional".to_string(), Value::Bool(false));
         }
 
         // Expand tech and notable settings
@@ -131,6 +407,10 @@ impl SeedData {
         let skill_assumption = get_key(settings, "skill_assumption_settings")?;
         let tech_settings_v = get_key(skill_assumption, "tech_settings")?;
         let tech_settings: HashSet<i32> = serde_json::from_value(tech_settings_v.take())?;
+        let known_tech: HashSet<i32> = preset_data.tech_data_map.iter().map(|(_, data)| data.tech_id).collect();
+        let mut missing_tech: Vec<i32> = tech_settings.difference(&known_tech).copied().collect();
+        missing_tech.sort();
+        load_warnings.extend(missing_tech.into_iter().map(MapErrorType::TechMissing));
         let tech_full: Vec<_> = preset_data.tech_data_map.iter().map(|(_, data)| {
             TechSetting {
                 id: data.tech_id,
@@ -142,6 +422,10 @@ impl SeedData {
         // notable
         let notable_settings_v = get_key(skill_assumption, "notable_settings")?;
         let notable_settings: HashSet<(usize, usize)> = serde_json::from_value(notable_settings_v.take())?;
+        let known_notables: HashSet<(usize, usize)> = preset_data.notable_data_map.iter().map(|(_, data)| (data.room_id, data.notable_id)).collect();
+        let mut missing_notables: Vec<(usize, usize)> = notable_settings.difference(&known_notables).copied().collect();
+        missing_notables.sort();
+        load_warnings.extend(missing_notables.into_iter().map(|(room_id, notable_id)| MapErrorType::NotableMissing(room_id, notable_id)));
         let notable_full: Vec<_> = preset_data.notable_data_map.iter().map(|(_, data)| {
             NotableSetting {
                 room_id: data.room_id,
@@ -182,7 +466,8 @@ impl SeedData {
         let settings_upgraded = try_upgrade_settings(settings_str, preset_data, false)?.0;
         *settings = serde_json::from_str(&settings_upgraded)?;
 
-        let seed_data: SeedData = serde_json::from_value(v)?;
+        let mut seed_data: SeedData = serde_json::from_value(v)?;
+        seed_data.load_warnings = load_warnings;
 
         Ok(seed_data)
     }
@@ -250,22 +535,37 @@ impl SeedData {
         Ok(seed_data)
     }
 
-    pub fn to_json(self) -> Result<Value> {
+    pub fn to_json(self, game_data: &GameData) -> Result<Value> {
+        // Item placements and spoiler overrides are keyed by location so they stay in place if the game data reorders its item locations
+        let item_placements: Vec<ItemPlacementEntry> = self.item_placements.iter().zip(game_data.item_locations.iter()).filter_map(|(&item, &(room_id, node_id))| {
+            if item == Item::Nothing { None } else { Some(ItemPlacementEntry { room_id, node_id, item }) }
+        }).collect();
+        let spoiler_overrides: Vec<SpoilerOverrideEntry> = self.spoiler_overrides.iter().map(|x| {
+            let (room_id, node_id) = game_data.item_locations[x.item_idx];
+            SpoilerOverrideEntry { step: x.step, room_id, node_id, description: x.description.clone() }
+        }).collect();
+        let flag_overrides: Vec<SpoilerFlagOverrideEntry> = self.spoiler_flag_overrides.iter().map(|x| {
+            SpoilerFlagOverrideEntry { step: x.step, flag: game_data.flag_isv.keys[x.flag_id].clone(), description: x.description.clone() }
+        }).collect();
+
+        let mut v = self.to_json_compact()?;
+        let obj = as_obj(&mut v)?;
+        obj.insert("format".to_string(), Value::String(Self::FORMAT.to_string()));
+        obj.insert("item_placements".to_string(), serde_json::to_value(item_placements)?);
+        obj.insert("spoiler_overrides".to_string(), serde_json::to_value(spoiler_overrides)?);
+        obj.insert("spoiler_flag_overrides".to_string(), serde_json::to_value(flag_overrides)?);
+
+        Ok(v)
+    }
+
+    /// Compacted map, door locks and settings, as stored since format 0.2.2
+    fn to_json_compact(self) -> Result<Value> {
         let mut v: Value = serde_json::to_value(&self)?;
 
         // Compact room_mask in map down from a Vec<bool> to a Bitmask
         let map = v.get_mut("map").ok_or(anyhow!("Expected key: map"))?;
         let room_mask_v = map.get_mut("room_mask").ok_or(anyhow!("Expected key: room_mask"))?;
-        let mut room_mask = vec![0u8; self.map.room_mask.len().div_ceil(8)];
-        for (i, b) in self.map.room_mask.into_iter().enumerate() {
-            if !b {
-                continue;
-            }
-            let idx = i / 8;
-            let offset = i % 8;
-            room_mask[idx] |= 1 << offset;
-        }
-        *room_mask_v = serde_json::to_value(room_mask)?;
+        *room_mask_v = serde_json::to_value(compact_room_mask(&self.map.room_mask))?;
 
         // dst_ptr_pair and bidirectional are unused for door locks
         let door_locks = v.get_mut("door_locks").ok_or(anyhow!("Expected key: door_locks"))?;
@@ -312,8 +612,106 @@ impl SeedData {
         Ok(v)
     }
 
-    pub fn save_to_file(self, path: &Path) -> Result<()> {
-        let v = self.to_json()?;
+    /// Semantic differences from self to other: Rooms, areas, door locks, items, start location and settings
+    pub fn diff(&self, other: &SeedData) -> Result<Vec<SeedDiffEntry>> {
+        let mut res = Vec::new();
+
+        let old_map = &self.map;
+        let new_map = &other.map;
+        if old_map.rooms.len() != new_map.rooms.len() {
+            bail!("Seeds were made with a different number of rooms");
+        }
+        for room_idx in 0..old_map.rooms.len() {
+            let old_active = old_map.room_mask.get(room_idx).copied().unwrap_or(true);
+            let new_active = new_map.room_mask.get(room_idx).copied().unwrap_or(true);
+            match (old_active, new_active) {
+                (false, false) => continue,
+                (false, true) => {
+                    res.push(SeedDiffEntry::RoomAdded(room_idx));
+                    continue;
+                }
+                (true, false) => {
+                    res.push(SeedDiffEntry::RoomRemoved(room_idx));
+                    continue;
+                }
+                (true, true) => {}
+            }
+            if old_map.rooms[room_idx] != new_map.rooms[room_idx] {
+                res.push(SeedDiffEntry::RoomMoved(room_idx, old_map.rooms[room_idx], new_map.rooms[room_idx]));
+            }
+            let old_area = (old_map.area[room_idx], old_map.subarea[room_idx], old_map.subsubarea[room_idx]);
+            let new_area = (new_map.area[room_idx], new_map.subarea[room_idx], new_map.subsubarea[room_idx]);
+            if old_area != new_area {
+                res.push(SeedDiffEntry::AreaChanged(room_idx, Area::from_tuple(old_area), Area::from_tuple(new_area)));
+            }
+        }
+
+        // Door locks are identified by their source door
+        let old_locks: HashMap<DoorPtrPair, DoorType> = self.door_locks.iter().map(|lock| (lock.src_ptr_pair, lock.door_type)).collect();
+        let new_locks: HashMap<DoorPtrPair, DoorType> = other.door_locks.iter().map(|lock| (lock.src_ptr_pair, lock.door_type)).collect();
+        for lock in &self.door_locks {
+            match new_locks.get(&lock.src_ptr_pair) {
+                None => res.push(SeedDiffEntry::DoorLockRemoved(lock.src_ptr_pair, lock.door_type)),
+                Some(new_type) if Placeable::from_door_type(*new_type) != Placeable::from_door_type(lock.door_type) => {
+                    res.push(SeedDiffEntry::DoorLockChanged(lock.src_ptr_pair, lock.door_type, *new_type));
+                }
+                _ => {}
+            }
+        }
+        for lock in &other.door_locks {
+            if !old_locks.contains_key(&lock.src_ptr_pair) {
+                res.push(SeedDiffEntry::DoorLockAdded(lock.src_ptr_pair, lock.door_type));
+            }
+        }
+
+        for (item_loc, (old_item, new_item)) in self.item_placements.iter().zip(other.item_placements.iter()).enumerate() {
+            if old_item != new_item {
+                res.push(SeedDiffEntry::ItemChanged(item_loc, *old_item, *new_item));
+            }
+        }
+
+        if self.start_location != other.start_location {
+            res.push(SeedDiffEntry::StartLocationChanged(self.start_location, other.start_location));
+        }
+
+        let mut old_settings = BTreeMap::new();
+        let mut new_settings = BTreeMap::new();
+        flatten_value(String::new(), get_readable_settings(&self.settings)?, &mut old_settings);
+        flatten_value(String::new(), get_readable_settings(&other.settings)?, &mut new_settings);
+        old_settings.insert("custom_escape_time".to_string(), serde_json::to_value(self.custom_escape_time)?.to_string());
+        new_settings.insert("custom_escape_time".to_string(), serde_json::to_value(other.custom_escape_time)?.to_string());
+        old_settings.insert("objectives".to_string(), serde_json::to_value(&self.objectives)?.to_string());
+        new_settings.insert("objectives".to_string(), serde_json::to_value(&other.objectives)?.to_string());
+        old_settings.insert("rng_seed".to_string(), serde_json::to_value(self.rng_seed)?.to_string());
+        new_settings.insert("rng_seed".to_string(), serde_json::to_value(other.rng_seed)?.to_string());
+        for (path, old_val) in &old_settings {
+            let new_val = new_settings.get(path).map(|s| s.as_str()).unwrap_or("None");
+            if old_val != new_val {
+                res.push(SeedDiffEntry::SettingChanged(path.clone(), old_val.clone(), new_val.to_string()));
+            }
+        }
+        for (path, new_val) in &new_settings {
+            if !old_settings.contains_key(path) {
+                res.push(SeedDiffEntry::SettingChanged(path.clone(), "None".to_string(), new_val.clone()));
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Seed file JSON compressed with zstd and encoded as URL-safe base64, short enough to be pasted in chat
+    pub fn to_share_code(self, game_data: &GameData) -> Result<String> {
+        let v = self.to_json(game_data)?;
+        let json = serde_json::to_vec(&v)?;
+
+        let mut data = vec![Self::SHARE_CODE_VERSION];
+        data.extend(zstd::encode_all(json.as_slice(), 19)?);
+
+        Ok(URL_SAFE_NO_PAD.encode(data))
+    }
+
+    pub fn save_to_file(self, path: &Path, game_data: &GameData) -> Result<()> {
+        let v = self.to_json(game_data)?;
         let s = serde_json::to_string_pretty(&v)?;
 
         let mut f = std::fs::File::create(path)?;
@@ -323,6 +721,175 @@ impl SeedData {
     }
 }
 
+/// Hash of the rooms, nodes, item locations, tech, notables, flags and strats of the game data. Seeds store it to detect that they were made with different game data
+pub fn get_game_data_fingerprint(game_data: &GameData) -> String {
+    // FNV-1a, the std hashers are not guaranteed to be stable between Rust versions
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut write = |bytes: &[u8]| {
+        for &b in bytes {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    };
+
+    for room in &game_data.room_geometry {
+        write(room.name.as_bytes());
+    }
+    let mut nodes: Vec<&(RoomId, NodeId)> = game_data.node_json_map.keys().collect();
+    nodes.sort();
+    for &(room_id, node_id) in nodes {
+        write(&room_id.to_le_bytes());
+        write(&node_id.to_le_bytes());
+    }
+    for &(room_id, node_id) in &game_data.item_locations {
+        write(&room_id.to_le_bytes());
+        write(&node_id.to_le_bytes());
+    }
+    let mut tech = game_data.tech_isv.keys.clone();
+    tech.sort();
+    for tech_id in tech {
+        write(&tech_id.to_le_bytes());
+    }
+    let mut notables = game_data.notable_isv.keys.clone();
+    notables.sort();
+    for (room_id, notable_id) in notables {
+        write(&room_id.to_le_bytes());
+        write(&notable_id.to_le_bytes());
+    }
+    for flag in &game_data.flag_isv.keys {
+        write(flag.as_bytes());
+    }
+    for link in &game_data.links {
+        for vertex_id in [link.from_vertex_id, link.to_vertex_id] {
+            let key = &game_data.vertex_isv.keys[vertex_id];
+            write(&key.room_id.to_le_bytes());
+            write(&key.node_id.to_le_bytes());
+        }
+        write(link.strat_name.as_bytes());
+        write(format!("{:?}", link.requirement).as_bytes());
+    }
+
+    format!("{hash:016x}")
+}
+
+pub(crate) fn get_item_name(item: Item) -> String {
+    match Placeable::from_item(item) {
+        Ok(placeable) => placeable.to_string(),
+        Err(_) => "Nothing".to_string()
+    }
+}
+
+fn get_door_type_name(door_type: DoorType) -> String {
+    match Placeable::from_door_type(door_type) {
+        Some(placeable) => placeable.to_string(),
+        None => "Blue Door".to_string()
+    }
+}
+
+fn get_node_name(game_data: &GameData, room_id: RoomId, node_id: NodeId) -> String {
+    let room_idx = game_data.room_idx_by_id[&room_id];
+    let room_name = &game_data.room_geometry[room_idx].name;
+    match game_data.node_json_map.get(&(room_id, node_id)).and_then(|node| node["name"].as_str()) {
+        Some(node_name) => format!("{room_name}: {node_name}"),
+        None => format!("{room_name}: Node {node_id}")
+    }
+}
+
+fn get_door_name(game_data: &GameData, ptr_pair: DoorPtrPair) -> String {
+    match game_data.door_ptr_pair_map.get(&ptr_pair) {
+        Some(&(room_id, node_id)) => get_node_name(game_data, room_id, node_id),
+        None => format!("{ptr_pair:?}")
+    }
+}
+
+fn get_start_location_name(game_data: &GameData, start_location: (RoomId, NodeId)) -> String {
+    let ship_start = Plando::get_ship_start();
+    if start_location == (ship_start.room_id, ship_start.node_id) {
+        return "Ship".to_string();
+    }
+    match game_data.start_location_id_map.get(&start_location) {
+        Some(&idx) => game_data.start_locations[idx].name.clone(),
+        None => get_node_name(game_data, start_location.0, start_location.1)
+    }
+}
+
+/// Settings as JSON with tech, notables and objectives keyed by their name instead of list position
+fn get_readable_settings(settings: &RandomizerSettings) -> Result<Value> {
+    let mut v = serde_json::to_value(settings)?;
+
+    let skill_assumption = get_key(&mut v, "skill_assumption_settings")?;
+    let tech: serde_json::Map<String, Value> = settings.skill_assumption_settings.tech_settings.iter().map(|tech| {
+        (tech.name.clone(), Value::Bool(tech.enabled))
+    }).collect();
+    *get_key(skill_assumption, "tech_settings")? = Value::Object(tech);
+    let notables: serde_json::Map<String, Value> = settings.skill_assumption_settings.notable_settings.iter().map(|notable| {
+        (format!("{}: {}", notable.room_name, notable.notable_name), Value::Bool(notable.enabled))
+    }).collect();
+    *get_key(skill_assumption, "notable_settings")? = Value::Object(notables);
+
+    let objective_settings = get_key(&mut v, "objective_settings")?;
+    let mut objectives = serde_json::Map::new();
+    for obj in &settings.objective_settings.objective_options {
+        let name = match serde_json::to_value(&obj.objective)? {
+            Value::String(s) => s,
+            other => other.to_string()
+        };
+        objectives.insert(name, serde_json::to_value(&obj.setting)?);
+    }
+    *get_key(objective_settings, "objective_options")? = Value::Object(objectives);
+
+    Ok(v)
+}
+
+/// Flattens nested objects and arrays into "a.b[0].c" paths to leaf values
+fn flatten_value(path: String, v: Value, out: &mut BTreeMap<String, String>) {
+    match v {
+        Value::Object(obj) => {
+            for (k, v) in obj {
+                let sub_path = if path.is_empty() { k } else { format!("{path}.{k}") };
+                flatten_value(sub_path, v, out);
+            }
+        }
+        Value::Array(arr) => {
+            for (idx, v) in arr.into_iter().enumerate() {
+                flatten_value(format!("{path}[{idx}]"), v, out);
+            }
+        }
+        leaf => {
+            out.insert(path, leaf.to_string());
+        }
+    }
+}
+
+type Migration = fn(Value, &GameData, &PresetData) -> Result<Value>;
+
+/// Migrations from every past format to the next one, in order
+const MIGRATIONS: [(&str, Migration); 3] = [
+    ("0.2.1", SeedData::migrate_0_2_1),
+    ("0.2.2", SeedData::migrate_0_2_2),
+    ("0.3.0", SeedData::migrate_0_3_0),
+];
+
+fn parse_format(format: &str) -> Result<(u32, u32, u32)> {
+    let parts: Vec<u32> = format.split('.').map(|x| x.parse::<u32>()).collect::<Result<_, _>>()
+        .map_err(|_| anyhow!("Invalid seed format \"{format}\""))?;
+    match parts.as_slice() {
+        &[major, minor, patch] => Ok((major, minor, patch)),
+        _ => bail!("Invalid seed format \"{format}\"")
+    }
+}
+
+/// Compacts room_mask from a Vec<bool> down to a Bitmask
+fn compact_room_mask(room_mask: &[bool]) -> Vec<u8> {
+    let mut res = vec![0u8; room_mask.len().div_ceil(8)];
+    for (i, &b) in room_mask.iter().enumerate() {
+        if b {
+            res[i / 8] |= 1 << (i % 8);
+        }
+    }
+    res
+}
+
 fn get_key<'a>(v: &'a mut Value, k: &str) -> Result<&'a mut Value> {
     v.get_mut(k).ok_or(anyhow!("Expected key: \"{k}\""))
 }
@@ -0,0 +1,254 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, bail, Result};
+use maprando::randomize::LockedDoor;
+use maprando_game::{DoorPtrPair, GameData, Item};
+use serde_json::Value;
+
+use crate::{analysis::get_item_location_name, seed_data::{get_item_name, SeedData}, Placeable};
+
+/// A change made differently by both branches. The merged seed contains the change from "ours"
+#[derive(Clone)]
+pub enum MergeConflict {
+    Room(usize), // room_idx: Position, area or door connections
+    ItemLocation(usize, Item, Item), // item_loc, ours, theirs
+    DuplicateItem(Item, usize, usize), // Unique item placed at a different location by both versions (item, ours item_loc, theirs item_loc)
+    DoorLock(DoorPtrPair),
+    StartLocation,
+    SpoilerOverrides,
+    Setting(String),
+}
+
+impl MergeConflict {
+    pub fn to_string(&self, game_data: &GameData) -> String {
+        match self {
+            MergeConflict::Room(room_idx) => format!("Room {} was changed in both versions", game_data.room_geometry[*room_idx].name),
+            MergeConflict::ItemLocation(item_loc, ours, theirs) => {
+                let name = get_item_location_name(game_data, *item_loc);
+                format!("Item at {name} was changed in both versions: {} and {}", get_item_name(*ours), get_item_name(*theirs))
+            }
+            MergeConflict::DuplicateItem(item, ours_loc, theirs_loc) => {
+                let ours_name = get_item_location_name(game_data, *ours_loc);
+                let theirs_name = get_item_location_name(game_data, *theirs_loc);
+                format!("{} was placed at {ours_name} and {theirs_name} in the two versions, only {ours_name} is kept", get_item_name(*item))
+            }
+            MergeConflict::DoorLock(ptr_pair) => {
+                let door_name = match game_data.room_and_door_idxs_by_door_ptr_pair.get(ptr_pair) {
+                    Some(&(room_idx, door_idx)) => format!("{} (Door {door_idx})", game_data.room_geometry[room_idx].name),
+                    None => format!("{ptr_pair:?}")
+                };
+                format!("Door lock at {door_name} was changed in both versions")
+            }
+            MergeConflict::StartLocation => format!("Start location was changed in both versions"),
+            MergeConflict::SpoilerOverrides => format!("Spoiler overrides were changed in both versions"),
+            MergeConflict::Setting(key) => format!("Setting {key} was changed in both versions")
+        }
+    }
+}
+
+pub struct SeedMerge {
+    pub seed_data: SeedData,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Three-way merge of a single value. Returns the merged value and whether both sides changed it differently
+fn merge3<T: Clone>(base: &T, ours: &T, theirs: &T, eq: impl Fn(&T, &T) -> bool) -> (T, bool) {
+    if eq(ours, theirs) || eq(base, theirs) {
+        (ours.clone(), false)
+    } else if eq(base, ours) {
+        (theirs.clone(), false)
+    } else {
+        (ours.clone(), true)
+    }
+}
+
+/// The door each door is connected to, in both directions. The flags are bidirectional and whether the key is the source of the connection
+fn get_door_connections(seed_data: &SeedData) -> BTreeMap<DoorPtrPair, (DoorPtrPair, bool, bool)> {
+    let mut res = BTreeMap::new();
+    for &(src, dst, bidirectional) in &seed_data.map.doors {
+        res.insert(src, (dst, bidirectional, true));
+        res.insert(dst, (src, bidirectional, false));
+    }
+    res
+}
+
+fn get_door_locks(seed_data: &SeedData) -> BTreeMap<DoorPtrPair, LockedDoor> {
+    seed_data.door_locks.iter().map(|lock| (lock.src_ptr_pair, lock.clone())).collect()
+}
+
+fn is_same_lock(a: &Option<LockedDoor>, b: &Option<LockedDoor>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => Placeable::from_door_type(a.door_type) == Placeable::from_door_type(b.door_type),
+        _ => false
+    }
+}
+
+/// Merges the changes made in "ours" and "theirs" since "base". Changes to different rooms, item locations, door locks and settings are combined,
+/// changes to the same one are reported as conflicts. The merged map should be validated afterwards since independent room changes can still overlap
+pub fn merge_seeds(base: &SeedData, ours: &SeedData, theirs: &SeedData, game_data: &GameData) -> Result<SeedMerge> {
+    let room_count = base.map.rooms.len();
+    if ours.map.rooms.len() != room_count || theirs.map.rooms.len() != room_count {
+        bail!("Seeds were made with a different number of rooms");
+    }
+    let item_count = base.item_placements.len();
+    if ours.item_placements.len() != item_count || theirs.item_placements.len() != item_count {
+        bail!("Seeds were made with a different number of item locations");
+    }
+
+    let mut conflicts = Vec::new();
+    let mut room_conflicts = BTreeSet::new();
+    let mut merged = ours.clone();
+
+    // Rooms: Mask, position and area
+    let room_state = |seed: &SeedData, idx: usize| {
+        let map = &seed.map;
+        (map.room_mask.get(idx).copied().unwrap_or(true), map.rooms[idx], map.area[idx], map.subarea[idx], map.subsubarea[idx])
+    };
+    for room_idx in 0..room_count {
+        let (state, conflict) = merge3(&room_state(base, room_idx), &room_state(ours, room_idx), &room_state(theirs, room_idx), |a, b| a == b);
+        if conflict {
+            room_conflicts.insert(room_idx);
+        }
+        let (active, pos, area, subarea, subsubarea) = state;
+        if room_idx < merged.map.room_mask.len() {
+            merged.map.room_mask[room_idx] = active;
+        }
+        merged.map.rooms[room_idx] = pos;
+        merged.map.area[room_idx] = area;
+        merged.map.subarea[room_idx] = subarea;
+        merged.map.subsubarea[room_idx] = subsubarea;
+    }
+
+    // Door connections are merged per door. A connection is only kept if both of its doors agree on it
+    let door_room_idx = |ptr_pair: &DoorPtrPair| game_data.room_and_door_idxs_by_door_ptr_pair.get(ptr_pair).map(|x| x.0);
+    let base_conns = get_door_connections(base);
+    let ours_conns = get_door_connections(ours);
+    let theirs_conns = get_door_connections(theirs);
+    let all_doors: BTreeSet<DoorPtrPair> = base_conns.keys().chain(ours_conns.keys()).chain(theirs_conns.keys()).copied().collect();
+    let mut merged_conns = BTreeMap::new();
+    for door in &all_doors {
+        let (conn, conflict) = merge3(&base_conns.get(door), &ours_conns.get(door), &theirs_conns.get(door), |a, b| a == b);
+        if conflict {
+            room_conflicts.extend(door_room_idx(door));
+        }
+        if let Some(&conn) = conn {
+            merged_conns.insert(*door, conn);
+        }
+    }
+    let mut doors = Vec::new();
+    for (door, &(other, bidirectional, is_src)) in &merged_conns {
+        let Some(&(other_conn, _, _)) = merged_conns.get(&other) else {
+            room_conflicts.extend(door_room_idx(door));
+            continue;
+        };
+        if other_conn != *door {
+            room_conflicts.extend(door_room_idx(door));
+            continue;
+        }
+        if *door > other {
+            continue;
+        }
+        doors.push(if is_src { (*door, other, bidirectional) } else { (other, *door, bidirectional) });
+    }
+    merged.map.doors = doors;
+
+    // Items
+    for item_loc in 0..item_count {
+        let (item, conflict) = merge3(&base.item_placements[item_loc], &ours.item_placements[item_loc], &theirs.item_placements[item_loc], |a, b| a == b);
+        if conflict {
+            conflicts.push(MergeConflict::ItemLocation(item_loc, ours.item_placements[item_loc], theirs.item_placements[item_loc]));
+        }
+        merged.item_placements[item_loc] = item;
+    }
+    // A unique item moved by both versions would be placed twice, only the placement from ours is kept
+    for item_loc in 0..item_count {
+        let item = merged.item_placements[item_loc];
+        if !item.is_unique() || ours.item_placements[item_loc] == item {
+            continue;
+        }
+        let ours_loc = (0..item_count).find(|&loc| loc != item_loc && merged.item_placements[loc] == item && ours.item_placements[loc] == item);
+        if let Some(ours_loc) = ours_loc {
+            conflicts.push(MergeConflict::DuplicateItem(item, ours_loc, item_loc));
+            merged.item_placements[item_loc] = Item::Nothing;
+        }
+    }
+
+    // Door locks
+    let base_locks = get_door_locks(base);
+    let ours_locks = get_door_locks(ours);
+    let theirs_locks = get_door_locks(theirs);
+    let all_locks: BTreeSet<DoorPtrPair> = base_locks.keys().chain(ours_locks.keys()).chain(theirs_locks.keys()).copied().collect();
+    merged.door_locks.clear();
+    for door in all_locks {
+        let (lock, conflict) = merge3(&base_locks.get(&door).cloned(), &ours_locks.get(&door).cloned(), &theirs_locks.get(&door).cloned(), is_same_lock);
+        if conflict {
+            conflicts.push(MergeConflict::DoorLock(door));
+        }
+        merged.door_locks.extend(lock);
+    }
+
+    // Start location and spoiler overrides
+    let (start_location, conflict) = merge3(&base.start_location, &ours.start_location, &theirs.start_location, |a, b| a == b);
+    if conflict {
+        conflicts.push(MergeConflict::StartLocation);
+    }
+    merged.start_location = start_location;
+
+    let (spoiler_overrides, item_conflict) = merge3(&base.spoiler_overrides, &ours.spoiler_overrides, &theirs.spoiler_overrides, |a, b| a == b);
+    let (spoiler_flag_overrides, flag_conflict) = merge3(&base.spoiler_flag_overrides, &ours.spoiler_flag_overrides, &theirs.spoiler_flag_overrides, |a, b| a == b);
+    if item_conflict || flag_conflict {
+        conflicts.push(MergeConflict::SpoilerOverrides);
+    }
+    merged.spoiler_overrides = spoiler_overrides;
+    merged.spoiler_flag_overrides = spoiler_flag_overrides;
+
+    // Settings are merged per top level key of the RandomizerSettings
+    let base_settings = serde_json::to_value(&base.settings)?;
+    let ours_settings = serde_json::to_value(&ours.settings)?;
+    let theirs_settings = serde_json::to_value(&theirs.settings)?;
+    let mut merged_settings = ours_settings.clone();
+    let merged_settings_obj = merged_settings.as_object_mut().ok_or(anyhow!("Expected settings to be of type object"))?;
+    for (key, ours_v) in ours_settings.as_object().ok_or(anyhow!("Expected settings to be of type object"))? {
+        let (v, conflict) = merge3(&base_settings.get(key), &Some(ours_v), &theirs_settings.get(key), |a, b| a == b);
+        if conflict {
+            conflicts.push(MergeConflict::Setting(key.clone()));
+        }
+        merged_settings_obj.insert(key.clone(), v.cloned().unwrap_or(Value::Null));
+    }
+    merged.settings = serde_json::from_value(merged_settings)?;
+
+    let (custom_escape_time, conflict) = merge3(&base.custom_escape_time, &ours.custom_escape_time, &theirs.custom_escape_time, |a, b| a == b);
+    if conflict {
+        conflicts.push(MergeConflict::Setting("custom_escape_time".to_string()));
+    }
+    merged.custom_escape_time = custom_escape_time;
+
+    let (objectives, conflict) = merge3(&base.objectives, &ours.objectives, &theirs.objectives, |a, b| a == b);
+    if conflict {
+        conflicts.push(MergeConflict::Setting("objectives".to_string()));
+    }
+    merged.objectives = objectives;
+
+    let (rng_seed, conflict) = merge3(&base.rng_seed, &ours.rng_seed, &theirs.rng_seed, |a, b| a == b);
+    if conflict {
+        conflicts.push(MergeConflict::Setting("rng_seed".to_string()));
+    }
+    merged.rng_seed = rng_seed;
+
+    let base_pool = serde_json::to_value(&base.item_pool)?;
+    let ours_pool = serde_json::to_value(&ours.item_pool)?;
+    let theirs_pool = serde_json::to_value(&theirs.item_pool)?;
+    let (item_pool, conflict) = merge3(&base_pool, &ours_pool, &theirs_pool, |a, b| a == b);
+    if conflict {
+        conflicts.push(MergeConflict::Setting("item_pool".to_string()));
+    }
+    merged.item_pool = serde_json::from_value(item_pool)?;
+
+    conflicts.splice(0..0, room_conflicts.into_iter().map(MergeConflict::Room));
+
+    Ok(SeedMerge {
+        seed_data: merged,
+        conflicts
+    })
+}
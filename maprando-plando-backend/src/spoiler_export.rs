@@ -0,0 +1,211 @@
+use hashbrown::HashMap;
+use maprando::spoiler_log::{SpoilerLocation, SpoilerLog};
+use maprando_game::{GameData, Map};
+use strum_macros::VariantArray;
+
+use crate::{randomize::get_vertex_info_by_id, SpoilerFlagOverride, SpoilerOverride};
+
+#[derive(Clone, Copy, PartialEq, Eq, VariantArray)]
+pub enum SpoilerExportFormat {
+    Text,
+    Markdown,
+    Csv
+}
+
+impl SpoilerExportFormat {
+    pub fn to_string(&self) -> String {
+        match self {
+            SpoilerExportFormat::Text => "Text",
+            SpoilerExportFormat::Markdown => "Markdown",
+            SpoilerExportFormat::Csv => "CSV"
+        }.to_string()
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SpoilerExportFormat::Text => "txt",
+            SpoilerExportFormat::Markdown => "md",
+            SpoilerExportFormat::Csv => "csv"
+        }
+    }
+}
+
+pub struct SpoilerReportLocation {
+    pub area: String,
+    pub room: String,
+    pub node: String
+}
+
+impl SpoilerReportLocation {
+    fn new(location: &SpoilerLocation, game_data: &GameData, map: &Map) -> Self {
+        let info = get_vertex_info_by_id(location.room_id, location.node_id, game_data, map);
+        SpoilerReportLocation {
+            area: info.area_name,
+            room: info.room_name,
+            node: info.node_name
+        }
+    }
+
+    fn to_string(&self) -> String {
+        format!("{}: {} ({})", self.room, self.node, self.area)
+    }
+}
+
+pub struct SpoilerReportItem {
+    pub item: String,
+    pub location: SpoilerReportLocation,
+    pub difficulty: Option<String>,
+    pub override_description: Option<String> // Set if the item is collected in this step because of a spoiler override
+}
+
+pub struct SpoilerReportFlag {
+    pub flag: String,
+    pub location: SpoilerReportLocation,
+    pub override_description: Option<String>
+}
+
+pub struct SpoilerReportStep {
+    pub step: usize,
+    pub items: Vec<SpoilerReportItem>,
+    pub flags: Vec<SpoilerReportFlag>
+}
+
+/// Step by step summary of a spoiler log with room and node names resolved for the current map, for sharing outside of the plando
+pub struct SpoilerReport {
+    pub steps: Vec<SpoilerReportStep>
+}
+
+impl SpoilerReport {
+    pub fn new(game_data: &GameData, map: &Map, spoiler_log: &SpoilerLog, spoiler_overrides: &[SpoilerOverride], spoiler_flag_overrides: &[SpoilerFlagOverride]) -> Self {
+        let item_overrides: HashMap<(usize, (usize, usize)), &str> = spoiler_overrides.iter().map(|x| {
+            ((x.step, game_data.item_locations[x.item_idx]), x.description.as_str())
+        }).collect();
+        let flag_overrides: HashMap<(usize, &str), &str> = spoiler_flag_overrides.iter().map(|x| {
+            ((x.step, game_data.flag_isv.keys[x.flag_id].as_str()), x.description.as_str())
+        }).collect();
+
+        let steps = spoiler_log.details.iter().map(|details| SpoilerReportStep {
+            step: details.step,
+            items: details.items.iter().map(|item| SpoilerReportItem {
+                item: item.item.clone(),
+                location: SpoilerReportLocation::new(&item.location, game_data, map),
+                difficulty: item.difficulty.clone(),
+                override_description: item_overrides.get(&(details.step, (item.location.room_id, item.location.node_id))).map(|x| x.to_string())
+            }).collect(),
+            flags: details.flags.iter().map(|flag| SpoilerReportFlag {
+                flag: flag.flag.clone(),
+                location: SpoilerReportLocation::new(&flag.location, game_data, map),
+                override_description: flag_overrides.get(&(details.step, flag.flag.as_str())).map(|x| x.to_string())
+            }).collect()
+        }).collect();
+
+        SpoilerReport { steps }
+    }
+
+    pub fn export(&self, format: SpoilerExportFormat) -> String {
+        match format {
+            SpoilerExportFormat::Text => self.to_text(),
+            SpoilerExportFormat::Markdown => self.to_markdown(),
+            SpoilerExportFormat::Csv => self.to_csv()
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        for step in &self.steps {
+            lines.push(format!("Step {}", step.step));
+            if !step.items.is_empty() {
+                lines.push("  Items:".to_string());
+            }
+            for item in &step.items {
+                let difficulty = item.difficulty.as_ref().map(|x| format!(" [{x}]")).unwrap_or_default();
+                lines.push(format!("    {} - {}{difficulty}", item.item, item.location.to_string()));
+                if let Some(desc) = &item.override_description {
+                    lines.push(format!("      Override: {desc}"));
+                }
+            }
+            if !step.flags.is_empty() {
+                lines.push("  Flags:".to_string());
+            }
+            for flag in &step.flags {
+                lines.push(format!("    {} - {}", flag.flag, flag.location.to_string()));
+                if let Some(desc) = &flag.override_description {
+                    lines.push(format!("      Override: {desc}"));
+                }
+            }
+            lines.push(String::new());
+        }
+        lines.join("\n")
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut lines = vec!["# Spoiler Log".to_string(), String::new()];
+        for step in &self.steps {
+            lines.push(format!("## Step {}", step.step));
+            lines.push(String::new());
+            if !step.items.is_empty() {
+                lines.push("| Item | Area | Room | Node | Difficulty | Override |".to_string());
+                lines.push("| --- | --- | --- | --- | --- | --- |".to_string());
+            }
+            for item in &step.items {
+                lines.push(format!("| {} | {} | {} | {} | {} | {} |",
+                    markdown_escape(&item.item),
+                    markdown_escape(&item.location.area),
+                    markdown_escape(&item.location.room),
+                    markdown_escape(&item.location.node),
+                    markdown_escape(item.difficulty.as_deref().unwrap_or_default()),
+                    markdown_escape(item.override_description.as_deref().unwrap_or_default())
+                ));
+            }
+            if !step.flags.is_empty() {
+                if !step.items.is_empty() {
+                    lines.push(String::new());
+                }
+                lines.push("| Flag | Area | Room | Node | Override |".to_string());
+                lines.push("| --- | --- | --- | --- | --- |".to_string());
+            }
+            for flag in &step.flags {
+                lines.push(format!("| {} | {} | {} | {} | {} |",
+                    markdown_escape(&flag.flag),
+                    markdown_escape(&flag.location.area),
+                    markdown_escape(&flag.location.room),
+                    markdown_escape(&flag.location.node),
+                    markdown_escape(flag.override_description.as_deref().unwrap_or_default())
+                ));
+            }
+            lines.push(String::new());
+        }
+        lines.join("\n")
+    }
+
+    /// One row per collected item: item, location, step and difficulty tier
+    pub fn to_csv(&self) -> String {
+        let mut lines = vec!["item,area,room,node,step,difficulty".to_string()];
+        for step in &self.steps {
+            for item in &step.items {
+                let fields = [
+                    item.item.as_str(),
+                    &item.location.area,
+                    &item.location.room,
+                    &item.location.node,
+                    &step.step.to_string(),
+                    item.difficulty.as_deref().unwrap_or_default()
+                ];
+                lines.push(fields.iter().map(|x| csv_escape(x)).collect::<Vec<_>>().join(","));
+            }
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+fn markdown_escape(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
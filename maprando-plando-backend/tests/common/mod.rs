@@ -0,0 +1,36 @@
+use std::{path::Path, sync::{Arc, OnceLock}};
+
+use maprando::{difficulty::{get_full_global, get_link_difficulty_length}, map_repository::MapRepository, preset::PresetData};
+use maprando_game::{GameData, Map};
+
+pub struct TestData {
+    pub game_data: Arc<GameData>,
+    pub preset_data: PresetData
+}
+
+/// Game data from the maprando-data directory created by setup.sh, with links prepared for the logic like the CLI does
+pub fn test_data() -> &'static TestData {
+    static DATA: OnceLock<TestData> = OnceLock::new();
+    DATA.get_or_init(|| {
+        let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/maprando-data");
+        std::env::set_current_dir(&data_dir).expect("maprando-data not found, run setup.sh first");
+
+        let mut game_data = GameData::load(Path::new(".")).unwrap();
+        let tech_path = Path::new("./data/tech_data.json");
+        let notable_path = Path::new("./data/notable_data.json");
+        let presets_path = Path::new("./data/presets/");
+        let preset_data = PresetData::load(tech_path, notable_path, presets_path, &game_data).unwrap();
+        let global = get_full_global(&game_data);
+        game_data.make_links_data(&|link, game_data| {
+            get_link_difficulty_length(link, game_data, &preset_data, &global)
+        });
+        TestData { game_data: Arc::new(game_data), preset_data }
+    })
+}
+
+/// The Vanilla map from the maps directory created by setup.sh
+pub fn vanilla_map() -> Map {
+    let data = test_data();
+    let maps_vanilla = MapRepository::new("Vanilla", Path::new("../maps/vanilla")).unwrap();
+    maps_vanilla.get_map_batch(0, &data.game_data).unwrap()[0].clone()
+}
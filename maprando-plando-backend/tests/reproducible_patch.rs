@@ -0,0 +1,102 @@
+mod common;
+
+use std::path::Path;
+
+use common::{test_data, vanilla_map};
+use maprando::{customize::{samus_sprite::SamusSpriteCategory, CustomizeSettings}, patch::Rom, settings::ObjectiveSetting};
+use maprando_game::Item;
+use maprando_plando_backend::{seed_data::SeedData, Plando};
+
+/// Seed file of the vanilla map with a few items placed, as it would be saved by the desktop app
+fn create_seed_json(rng_seed: u64) -> Vec<u8> {
+    let data = test_data();
+    let map = vanilla_map();
+
+    let mut plando = Plando::new(data.game_data.clone(), map, &data.preset_data, Path::new("../patches/mosaic")).unwrap();
+    plando.set_rng_seed(rng_seed);
+    plando.update_objectives();
+    for (item_loc, item) in [Item::Morph, Item::Missile, Item::Super, Item::PowerBomb].into_iter().enumerate() {
+        plando.place_item(item_loc, item);
+    }
+
+    let v = SeedData::from_plando(&plando).to_json(&data.game_data).unwrap();
+    serde_json::to_vec(&v).unwrap()
+}
+
+fn load_plando(seed_json: &[u8]) -> Plando {
+    let data = test_data();
+    let seed_data = SeedData::from_bytes(seed_json.to_vec(), &data.game_data, &data.preset_data).unwrap();
+    let mut plando = Plando::new(data.game_data.clone(), seed_data.map.clone(), &data.preset_data, Path::new("../patches/mosaic")).unwrap();
+    seed_data.load_into_plando(&mut plando).unwrap();
+    plando
+}
+
+async fn get_spoiler_json(plando: &mut Plando) -> String {
+    plando.prepare_map().unwrap();
+    plando.update_spoiler_data(true).unwrap().await.unwrap().unwrap();
+    let lock = plando.get_randomization();
+    let (_, spoiler_log) = lock.as_ref().unwrap();
+    serde_json::to_string(spoiler_log).unwrap()
+}
+
+#[tokio::test]
+async fn rng_seed_is_saved() {
+    let seed_json = create_seed_json(1234);
+    let plando = load_plando(&seed_json);
+    assert_eq!(plando.rng_seed, 1234);
+    assert_eq!(SeedData::from_plando(&plando).rng_seed, Some(1234));
+}
+
+#[tokio::test]
+async fn rolled_objectives_survive_setting_changes() {
+    let data = test_data();
+    let map = vanilla_map();
+
+    let mut plando = Plando::new(data.game_data.clone(), map, &data.preset_data, Path::new("../patches/mosaic")).unwrap();
+    plando.set_rng_seed(1234);
+    let mut settings = plando.randomizer_settings.clone();
+    for option in &mut settings.objective_settings.objective_options {
+        option.setting = ObjectiveSetting::Maybe;
+    }
+    settings.objective_settings.min_objectives = 4;
+    settings.objective_settings.max_objectives = 4;
+    settings.objective_settings.preset = None;
+
+    // Every settings change rolls the objectives again, which must not depend on how often that happened
+    plando.load_preset(settings.clone());
+    plando.load_preset(settings.clone());
+    plando.set_objectives(None);
+    let objectives = plando.objectives.clone();
+
+    let v = SeedData::from_plando(&plando).to_json(&data.game_data).unwrap();
+    let reloaded = load_plando(&serde_json::to_vec(&v).unwrap());
+    assert!(reloaded.objectives == objectives);
+}
+
+#[tokio::test]
+async fn spoiler_is_reproducible() {
+    let seed_json = create_seed_json(1234);
+    let mut first = load_plando(&seed_json);
+    let mut second = load_plando(&seed_json);
+    assert!(first.objectives == second.objectives);
+    assert_eq!(get_spoiler_json(&mut first).await, get_spoiler_json(&mut second).await);
+}
+
+/// Patching requires a vanilla ROM, which can't be part of the repository. Run with `PLANDO_TEST_ROM=<path> cargo test -- --ignored`
+#[tokio::test]
+#[ignore = "needs PLANDO_TEST_ROM"]
+async fn patching_twice_gives_identical_roms() {
+    let rom_path = std::env::var("PLANDO_TEST_ROM").expect("PLANDO_TEST_ROM must be set to the path of a vanilla ROM");
+    let rom_vanilla = Rom::new(std::fs::read(rom_path).unwrap());
+    let seed_json = create_seed_json(1234);
+    let samus_sprites_path = Path::new("../MapRandoSprites/samus_sprites/manifest.json");
+
+    let mut roms = Vec::new();
+    for _ in 0..2 {
+        let samus_sprites: Vec<SamusSpriteCategory> = serde_json::from_str(&std::fs::read_to_string(samus_sprites_path).unwrap()).unwrap();
+        let mut plando = load_plando(&seed_json);
+        let handle = plando.patch_rom(&rom_vanilla, CustomizeSettings::default(), samus_sprites, Vec::new()).unwrap();
+        roms.push(handle.await.unwrap().unwrap());
+    }
+    assert!(roms[0].data == roms[1].data, "Patched ROMs differ");
+}
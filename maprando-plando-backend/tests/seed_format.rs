@@ -0,0 +1,252 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::{test_data, vanilla_map};
+use hashbrown::HashSet;
+use maprando_game::{DoorType, Item};
+use maprando_plando_backend::{map_editor::MapErrorType, seed_data::SeedData, Placeable, Plando, SpoilerOverride};
+use serde_json::{json, Value};
+
+fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn read_fixture(name: &str) -> Value {
+    serde_json::from_str(&std::fs::read_to_string(fixture_path(name)).unwrap()).unwrap()
+}
+
+/// Fixtures only contain the settings relevant to their format, everything else is filled in from the default preset
+fn fill_defaults(v: &mut Value, defaults: &Value) {
+    let (Value::Object(obj), Value::Object(default_obj)) = (v, defaults) else {
+        return;
+    };
+    for (k, default_v) in default_obj {
+        match obj.get_mut(k) {
+            Some(v) => fill_defaults(v, default_v),
+            None => {
+                obj.insert(k.clone(), default_v.clone());
+            }
+        }
+    }
+}
+
+/// Fixtures only contain the first rooms of the map, the remaining rooms are taken from the Vanilla map so the room count matches the game data.
+/// Added rooms are always visible, in whichever room_mask layout the fixture uses
+fn fill_map(v: &mut Value) {
+    let vanilla = serde_json::to_value(vanilla_map()).unwrap();
+    let map = v.get_mut("map").unwrap();
+    let fixture_rooms = map["rooms"].as_array().unwrap().len();
+    let room_count = vanilla["rooms"].as_array().unwrap().len();
+
+    for key in ["rooms", "area", "subarea", "subsubarea"] {
+        let arr = map[key].as_array_mut().unwrap();
+        arr.extend(vanilla[key].as_array().unwrap()[fixture_rooms..].iter().cloned());
+    }
+    map["doors"] = vanilla["doors"].clone();
+
+    let Some(room_mask) = map.get_mut("room_mask").and_then(|x| x.as_array_mut()) else {
+        return;
+    };
+    if room_mask.first().is_some_and(|x| x.is_boolean()) {
+        room_mask.resize(room_count, Value::Bool(true));
+        return;
+    }
+    // Compacted into a bitmask
+    let mut bytes: Vec<u8> = room_mask.iter().map(|x| x.as_u64().unwrap() as u8).collect();
+    bytes.resize(room_count.div_ceil(8), 0);
+    for idx in fixture_rooms..room_count {
+        bytes[idx / 8] |= 1 << (idx % 8);
+    }
+    *room_mask = bytes.into_iter().map(Value::from).collect();
+}
+
+fn load_fixture(name: &str) -> anyhow::Result<SeedData> {
+    let data = test_data();
+    let mut v = read_fixture(name);
+    let defaults = serde_json::to_value(&data.preset_data.default_preset).unwrap();
+    fill_defaults(v.get_mut("settings").unwrap(), &defaults);
+    fill_map(&mut v);
+    SeedData::from_bytes(serde_json::to_vec(&v).unwrap(), &data.game_data, &data.preset_data)
+}
+
+fn check_fixture(seed_data: &SeedData) {
+    let data = test_data();
+
+    assert_eq!(seed_data.map.rooms.len(), data.game_data.room_geometry.len());
+    assert_eq!(seed_data.map.room_mask.len(), seed_data.map.rooms.len());
+
+    assert_eq!(seed_data.item_placements.len(), data.game_data.item_locations.len());
+    assert!(seed_data.item_placements[0] == Item::Morph);
+    assert!(seed_data.item_placements[5] == Item::Missile);
+    assert!(seed_data.item_placements[17] == Item::Super);
+    assert!(seed_data.item_placements[42] == Item::ETank);
+    assert!(seed_data.item_placements[99] == Item::PowerBomb);
+    assert_eq!(seed_data.item_placements.iter().filter(|&&item| item != Item::Nothing).count(), 5);
+
+    // Tech 1, 2 and 3 are enabled in every fixture
+    let tech_ids: HashSet<i32> = data.preset_data.tech_data_map.iter().map(|(_, tech)| tech.tech_id).collect();
+    let expected: HashSet<i32> = [1, 2, 3].into_iter().filter(|id| tech_ids.contains(id)).collect();
+    let enabled: HashSet<i32> = seed_data.settings.skill_assumption_settings.tech_settings.iter()
+        .filter(|tech| tech.enabled).map(|tech| tech.id).collect();
+    assert_eq!(enabled, expected);
+    assert_eq!(seed_data.settings.skill_assumption_settings.tech_settings.len(), tech_ids.len());
+
+    assert!(!seed_data.load_warnings.iter().any(|x| matches!(x, MapErrorType::DoorLockMissing(_))));
+}
+
+/// Seed file saved from the Vanilla map in the current format, with the same items and spoiler override as the fixtures
+fn save_seed_json() -> Value {
+    let data = test_data();
+    let mut plando = Plando::new(data.game_data.clone(), vanilla_map(), &data.preset_data, Path::new("../patches/mosaic")).unwrap();
+    for (item_loc, item) in [(0, Item::Morph), (5, Item::Missile), (17, Item::Super), (42, Item::ETank), (99, Item::PowerBomb)] {
+        plando.place_item(item_loc, item);
+    }
+    plando.spoiler_overrides.push(SpoilerOverride { step: 1, item_idx: 5, description: "Fixture override".to_string() });
+    SeedData::from_plando(&plando).to_json(&data.game_data).unwrap()
+}
+
+/// Every fixture from 0.2.2 on hides rooms 3 and 7
+fn check_room_mask(seed_data: &SeedData) {
+    let hidden: Vec<usize> = seed_data.map.room_mask.iter().enumerate().filter(|(_, &b)| !b).map(|(idx, _)| idx).collect();
+    assert_eq!(hidden, [3, 7]);
+}
+
+#[test]
+fn detect_format_of_fixtures() {
+    assert_eq!(SeedData::detect_format(&read_fixture("seed-0.2.1.json")).unwrap(), "0.2.1");
+    assert_eq!(SeedData::detect_format(&read_fixture("seed-0.2.2-v119.json")).unwrap(), "0.2.2");
+    assert_eq!(SeedData::detect_format(&read_fixture("seed-0.2.2.json")).unwrap(), "0.2.2");
+    assert_eq!(SeedData::detect_format(&read_fixture("seed-0.3.0.json")).unwrap(), "0.3.0");
+}
+
+#[test]
+fn load_format_0_2_1() {
+    let seed_data = load_fixture("seed-0.2.1.json").unwrap();
+    check_fixture(&seed_data);
+
+    let start_location = &test_data().game_data.start_locations[0];
+    assert_eq!(seed_data.start_location, (start_location.room_id, start_location.node_id));
+    assert!(seed_data.map.room_mask.iter().all(|&b| b));
+    assert!(seed_data.door_locks.is_empty());
+}
+
+#[test]
+fn load_format_0_2_2_v119() {
+    let seed_data = load_fixture("seed-0.2.2-v119.json").unwrap();
+    check_fixture(&seed_data);
+    check_room_mask(&seed_data);
+
+    assert_eq!(seed_data.start_location, (8, 5));
+    assert_eq!(seed_data.door_locks.len(), 1);
+    assert!(matches!(seed_data.door_locks[0].door_type, DoorType::Red));
+    assert_eq!(seed_data.creator_name, "Fixture");
+}
+
+#[test]
+fn load_format_0_2_2() {
+    let seed_data = load_fixture("seed-0.2.2.json").unwrap();
+    check_fixture(&seed_data);
+    check_room_mask(&seed_data);
+
+    assert_eq!(seed_data.start_location, (8, 5));
+    assert_eq!(seed_data.door_locks.len(), 1);
+    assert!(matches!(seed_data.door_locks[0].door_type, DoorType::Red));
+    assert_eq!(seed_data.spoiler_overrides.len(), 1);
+    assert_eq!(seed_data.spoiler_overrides[0].item_idx, 5);
+}
+
+#[test]
+fn load_format_0_3_0() {
+    let seed_data = load_fixture("seed-0.3.0.json").unwrap();
+    check_fixture(&seed_data);
+    check_room_mask(&seed_data);
+
+    assert_eq!(seed_data.door_locks.len(), 1);
+    assert!(matches!(seed_data.door_locks[0].door_type, DoorType::Red));
+    let item_pool = seed_data.item_pool.as_ref().unwrap();
+    assert!(item_pool.iter().any(|x| x.item == Item::Missile && x.count == 46));
+}
+
+#[test]
+fn save_writes_current_format() {
+    let seed_data = load_fixture("seed-0.2.1.json").unwrap();
+    let v = seed_data.clone().to_json(&test_data().game_data).unwrap();
+    assert_eq!(v["format"].as_str(), Some(SeedData::FORMAT));
+
+    let data = test_data();
+    let reloaded = SeedData::from_bytes(serde_json::to_vec(&v).unwrap(), &data.game_data, &data.preset_data).unwrap();
+    check_fixture(&reloaded);
+    assert_eq!(reloaded.start_location, seed_data.start_location);
+}
+
+#[test]
+fn newer_format_is_rejected() {
+    let err = load_fixture("seed-99.0.0.json").err().unwrap();
+    assert!(err.to_string().contains("made with a newer plando version"), "{err}");
+}
+
+#[test]
+fn item_placements_are_keyed_by_location() {
+    let data = test_data();
+    let v = save_seed_json();
+    assert_eq!(v["format"].as_str(), Some(SeedData::FORMAT));
+
+    let placements = v["item_placements"].as_array().unwrap();
+    assert_eq!(placements.len(), 5);
+    let (room_id, node_id) = data.game_data.item_locations[42];
+    assert!(placements.iter().any(|x| x["room_id"] == room_id && x["node_id"] == node_id && x["item"] == "ETank"));
+
+    let overrides = v["spoiler_overrides"].as_array().unwrap();
+    let (room_id, node_id) = data.game_data.item_locations[5];
+    assert_eq!(overrides.len(), 1);
+    assert!(overrides[0]["room_id"] == room_id && overrides[0]["node_id"] == node_id);
+
+    let reloaded = SeedData::from_bytes(serde_json::to_vec(&v).unwrap(), &data.game_data, &data.preset_data).unwrap();
+    assert!(reloaded.item_placements[42] == Item::ETank);
+    assert_eq!(reloaded.spoiler_overrides[0].item_idx, 5);
+    assert!(reloaded.load_warnings.is_empty());
+}
+
+#[test]
+fn missing_locations_are_flagged() {
+    let data = test_data();
+    let mut v = save_seed_json();
+    v["item_placements"].as_array_mut().unwrap().push(json!({ "room_id": 99999, "node_id": 1, "item": "Missile" }));
+    v["spoiler_overrides"].as_array_mut().unwrap().push(json!({ "step": 2, "room_id": 99999, "node_id": 1, "description": "Missing" }));
+
+    let reloaded = SeedData::from_bytes(serde_json::to_vec(&v).unwrap(), &data.game_data, &data.preset_data).unwrap();
+    assert_eq!(reloaded.item_placements.iter().filter(|&&item| item != Item::Nothing).count(), 5);
+    assert_eq!(reloaded.spoiler_overrides.len(), 1);
+    assert!(reloaded.load_warnings.contains(&MapErrorType::ItemLocationMissing(99999, 1, Placeable::Missile)));
+    assert!(reloaded.load_warnings.contains(&MapErrorType::OverrideLocationMissing(99999, 1, 2)));
+}
+
+#[test]
+fn changed_game_data_is_flagged() {
+    let data = test_data();
+    let mut v = save_seed_json();
+    assert!(v["game_data_fingerprint"].is_string());
+
+    v["game_data_fingerprint"] = json!("0000000000000000");
+    v["maprando_commit"] = json!("0000000");
+    v["settings"]["skill_assumption_settings"]["tech_settings"].as_array_mut().unwrap().push(json!(999999));
+    v["start_location"] = json!([99999, 1]);
+
+    let reloaded = SeedData::from_bytes(serde_json::to_vec(&v).unwrap(), &data.game_data, &data.preset_data).unwrap();
+    assert!(reloaded.item_placements[42] == Item::ETank);
+    assert!(reloaded.load_warnings.contains(&MapErrorType::GameDataChanged));
+    assert!(reloaded.load_warnings.contains(&MapErrorType::TechMissing(999999)));
+    assert!(reloaded.load_warnings.contains(&MapErrorType::StartLocationMissing(99999, 1)));
+    assert_eq!(reloaded.start_location, (8, 5));
+}
+
+#[test]
+fn changed_room_count_is_rejected() {
+    let data = test_data();
+    let mut v = save_seed_json();
+    v["map"]["rooms"].as_array_mut().unwrap().pop();
+
+    let err = SeedData::from_bytes(serde_json::to_vec(&v).unwrap(), &data.game_data, &data.preset_data).err().unwrap();
+    assert!(err.to_string().contains("rooms, but the game data has"), "{err}");
+}
@@ -0,0 +1,446 @@
+use std::{collections::HashMap, fmt, io::Write, path::{Path, PathBuf}, process::ExitCode, sync::Arc};
+
+use anyhow::{anyhow, bail, Result};
+use maprando::{customize::{mosaic::MosaicTheme, samus_sprite::SamusSpriteCategory, CustomizeSettings}, difficulty::{get_full_global, get_link_difficulty_length}, patch::Rom, preset::PresetData};
+use maprando_game::GameData;
+use maprando_plando_backend::{seed_data::SeedData, seed_merge::merge_seeds, Plando};
+
+const USAGE: &str = "Usage: plando <command> <seed file> [key=value ...]
+
+Commands:
+  validate    Checks the map and logic of a seed file
+  spoiler     Computes the spoiler log and writes it to out=<file> (Default: stdout)
+  patch       Patches a vanilla ROM. Requires rom=<file> and out=<file>, customize=<file> is optional
+  difficulty  Finds the lowest skill preset the seed is beatable on and lists the items out of logic on lower presets
+  graph       Writes the progression dependency graph to out=<file> (Default: stdout)
+  merge       Three-way merge of ours=<file> and theirs=<file> with the seed file as base. Writes the result to out=<file>
+  diff        Lists the changes from the seed file to other=<file>: rooms, areas, door locks, items, start location and settings
+
+Options:
+  data=<dir>       Path to the maprando-data directory (Default: ./data/maprando-data/)
+  strict=true      validate: Treat warnings as errors
+  format=dot|json  graph: Output format (Default: dot)
+
+Exit codes:
+  0  Success
+  1  Invalid arguments or output could not be written
+  2  Failed to load game data or input files
+  3  Seed is invalid
+  4  Seed is not logically beatable
+  5  Spoiler computation or patching failed";
+
+/// Exit codes so the outcome can be checked by scripts
+#[derive(Clone, Copy)]
+enum Status {
+    Success = 0,
+    Usage = 1,
+    LoadFailed = 2,
+    Invalid = 3,
+    NotBeatable = 4,
+    Failed = 5
+}
+
+/// Invalid command line arguments, reported with exit code 1 instead of a load failure
+#[derive(Debug)]
+struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+/// Options that take a file or directory. They are made absolute while parsing, before load_data changes the working directory
+const PATH_OPTIONS: [&str; 7] = ["data", "out", "rom", "customize", "other", "ours", "theirs"];
+
+struct Args {
+    command: String,
+    seed_path: PathBuf,
+    options: HashMap<String, String>,
+    paths: HashMap<String, PathBuf>
+}
+
+impl Args {
+    fn parse() -> Result<Args> {
+        let mut args = std::env::args().skip(1);
+        let command = args.next().ok_or(anyhow!("Missing command"))?;
+        let seed_path = args.next().ok_or(anyhow!("Missing seed file"))?;
+
+        let mut options = HashMap::new();
+        let mut paths = HashMap::new();
+        for arg in args {
+            let Some((k, v)) = arg.split_once('=') else {
+                bail!("Invalid option \"{arg}\", expected key=value");
+            };
+            if PATH_OPTIONS.contains(&k) {
+                let path = std::path::absolute(v).map_err(|err| anyhow!("Invalid path \"{v}\": {err}"))?;
+                paths.insert(k.to_string(), path);
+            }
+            options.insert(k.to_string(), v.to_string());
+        }
+
+        Ok(Args {
+            command,
+            seed_path: std::path::absolute(&seed_path).map_err(|err| anyhow!("Invalid path \"{seed_path}\": {err}"))?,
+            options,
+            paths
+        })
+    }
+
+    fn get_path(&self, key: &str) -> Option<PathBuf> {
+        self.paths.get(key).cloned()
+    }
+
+    fn get_required_path(&self, key: &str) -> Result<PathBuf> {
+        self.get_path(key).ok_or(UsageError(format!("Missing option {key}=<file>")).into())
+    }
+
+    fn is_set(&self, key: &str) -> bool {
+        self.options.get(key).is_some_and(|v| v == "true" || v == "1")
+    }
+}
+
+struct CliData {
+    game_data: Arc<GameData>,
+    preset_data: PresetData
+}
+
+fn load_data(data_dir: &Path) -> Result<CliData> {
+    // GameData and the patcher expect to be run from inside the data directory
+    std::env::set_current_dir(data_dir)?;
+
+    let mut game_data = GameData::load(Path::new("."))?;
+    let tech_path = Path::new("./data/tech_data.json");
+    let notable_path = Path::new("./data/notable_data.json");
+    let presets_path = Path::new("./data/presets/");
+    let preset_data = PresetData::load(tech_path, notable_path, presets_path, &game_data)?;
+    let global = get_full_global(&game_data);
+    game_data.make_links_data(&|link, game_data| {
+        get_link_difficulty_length(link, game_data, &preset_data, &global)
+    });
+
+    Ok(CliData {
+        game_data: Arc::new(game_data),
+        preset_data
+    })
+}
+
+fn load_plando(data: &CliData, seed_path: &Path) -> Result<Plando> {
+    let seed_data = SeedData::from_file(seed_path, &data.game_data, &data.preset_data)?;
+    load_plando_from_seed(data, seed_data)
+}
+
+fn load_plando_from_seed(data: &CliData, seed_data: SeedData) -> Result<Plando> {
+    let toilet_path = Path::new("../patches/mosaic");
+    let mut plando = Plando::new(data.game_data.clone(), seed_data.map.clone(), &data.preset_data, toilet_path)?;
+    seed_data.load_into_plando(&mut plando)?;
+    Ok(plando)
+}
+
+fn load_samus_sprites() -> Result<Vec<SamusSpriteCategory>> {
+    let samus_sprites_path = Path::new("../MapRandoSprites/samus_sprites/manifest.json");
+    Ok(serde_json::from_str(&std::fs::read_to_string(samus_sprites_path)?)?)
+}
+
+fn get_mosaic_themes() -> Vec<MosaicTheme> {
+    vec![
+        ("OuterCrateria", "Outer Crateria"),
+        ("InnerCrateria", "Inner Crateria"),
+        ("BlueBrinstar", "Blue Brinstar"),
+        ("GreenBrinstar", "Green Brinstar"),
+        ("PinkBrinstar", "Pink Brinstar"),
+        ("RedBrinstar", "Red Brinstar"),
+        ("UpperNorfair", "Upper Norfair"),
+        ("LowerNorfair", "Lower Norfair"),
+        ("WreckedShip", "Wrecked Ship"),
+        ("WestMaridia", "West Maridia"),
+        ("YellowMaridia", "Yellow Maridia"),
+        ("MechaTourian", "Mecha Tourian"),
+        ("MetroidHabitat", "Metroid Habitat"),
+        ("Outline", "Practice Outlines"),
+        ("Invisible", "Invisible")
+    ].into_iter()
+        .map(|(x, y)| MosaicTheme {
+            name: x.to_string(),
+            display_name: y.to_string(),
+        }).collect()
+}
+
+/// Writes the result to out=<file> or stdout. Failing to write is reported as a usage error
+fn write_output(args: &Args, data: &[u8]) -> Result<()> {
+    let res = match args.get_path("out") {
+        Some(out_path) => std::fs::write(&out_path, data).map_err(|err| format!("Could not write {}: {err}", out_path.display())),
+        None => std::io::stdout().write_all(data).map_err(|err| format!("Could not write to stdout: {err}"))
+    };
+    res.map_err(|msg| UsageError(msg).into())
+}
+
+fn validate(args: &Args, data: &CliData) -> Result<Status> {
+    let mut plando = load_plando(data, &args.seed_path)?;
+    let strict = args.is_set("strict");
+
+    plando.map_editor.is_valid_full(&plando.locked_doors);
+    plando.check_item_pool();
+
+    let mut status = Status::Success;
+    for err in &plando.map_editor.error_list {
+        if err.is_severe() {
+            eprintln!("error: {}", err.to_string(&data.game_data));
+            status = Status::Invalid;
+        } else {
+            eprintln!("warning: {}", err.to_string(&data.game_data));
+            if strict {
+                status = Status::Invalid;
+            }
+        }
+    }
+
+    if let Err(err) = plando.is_map_logic_valid() {
+        eprintln!("error: {err}");
+        status = Status::Invalid;
+    }
+
+    Ok(status)
+}
+
+/// Loads the seed file and computes its spoiler log. Errors are printed, the inner Err is the status to exit with
+async fn load_plando_with_spoiler(args: &Args, data: &CliData) -> Result<Result<Plando, Status>> {
+    let mut plando = load_plando(data, &args.seed_path)?;
+
+    if let Err(err) = plando.prepare_map() {
+        eprintln!("error: {err}");
+        return Ok(Err(Status::Invalid));
+    }
+
+    let handle = match plando.update_spoiler_data(true) {
+        Ok(handle) => handle,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return Ok(Err(Status::Invalid));
+        }
+    };
+    if let Err(err) = handle.await? {
+        eprintln!("error: Failed to update spoiler data: {err}");
+        return Ok(Err(Status::Failed));
+    }
+
+    Ok(Ok(plando))
+}
+
+async fn spoiler(args: &Args, data: &CliData) -> Result<Status> {
+    let plando = match load_plando_with_spoiler(args, data).await? {
+        Ok(plando) => plando,
+        Err(status) => return Ok(status)
+    };
+
+    let mutex = plando.get_randomization();
+    let (_, s) = mutex.as_ref().ok_or(anyhow!("Could not generate spoiler data"))?;
+
+    let mb_flag_str = &data.game_data.flag_isv.keys[data.game_data.mother_brain_defeated_flag_id];
+    let mb_clearable = s.details.iter().any(|summary| {
+        summary.flags.iter().any(|flag| flag.flag == *mb_flag_str)
+    });
+
+    let mut s_json = serde_json::to_value(s)?;
+    let s_json_map = s_json.as_object_mut().unwrap();
+    s_json_map.retain(|k, _| k != "forward_traversal" && k != "reverse_traversal");
+    s_json_map.insert("spoiler_overrides".to_string(), serde_json::to_value(&plando.spoiler_overrides)?);
+    s_json_map.insert("spoiler_flag_overrides".to_string(), serde_json::to_value(&plando.spoiler_flag_overrides)?);
+    let s_str = serde_json::to_string_pretty(&s_json)?;
+
+    write_output(args, s_str.as_bytes())?;
+
+    if !mb_clearable {
+        eprintln!("warning: Mother Brain is not logically reachable");
+        return Ok(Status::NotBeatable);
+    }
+    Ok(Status::Success)
+}
+
+async fn patch(args: &Args, data: &CliData) -> Result<Status> {
+    let rom_path = args.get_required_path("rom")?;
+    let out_path = args.get_required_path("out")?;
+    let customize_settings: CustomizeSettings = match args.get_path("customize") {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => CustomizeSettings::default()
+    };
+
+    let rom_vanilla = Rom::new(std::fs::read(rom_path)?);
+    if rom_vanilla.data.len() == 0 {
+        bail!("ROM data is empty");
+    }
+    let samus_sprites = load_samus_sprites()?;
+
+    let mut plando = load_plando(data, &args.seed_path)?;
+    let handle = match plando.patch_rom(&rom_vanilla, customize_settings, samus_sprites, get_mosaic_themes()) {
+        Ok(handle) => handle,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return Ok(Status::Invalid);
+        }
+    };
+    let rom = match handle.await? {
+        Ok(rom) => rom,
+        Err(err) => {
+            eprintln!("error: Failed to patch ROM: {err}");
+            return Ok(Status::Failed);
+        }
+    };
+
+    std::fs::write(&out_path, rom.data).map_err(|err| UsageError(format!("Could not write {}: {err}", out_path.display())))?;
+    Ok(Status::Success)
+}
+
+async fn difficulty(args: &Args, data: &CliData) -> Result<Status> {
+    let mut plando = load_plando(data, &args.seed_path)?;
+
+    if let Err(err) = plando.prepare_map() {
+        eprintln!("error: {err}");
+        return Ok(Status::Invalid);
+    }
+
+    let handle = match plando.analyze_min_difficulty() {
+        Ok(handle) => handle,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return Ok(Status::Invalid);
+        }
+    };
+    let analysis = match handle.await? {
+        Ok(analysis) => analysis,
+        Err(err) => {
+            eprintln!("error: Failed to analyze difficulty: {err}");
+            return Ok(Status::Failed);
+        }
+    };
+
+    println!("{}", analysis.to_string(&data.game_data));
+
+    if analysis.min_tier.is_none() {
+        return Ok(Status::NotBeatable);
+    }
+    Ok(Status::Success)
+}
+
+async fn graph(args: &Args, data: &CliData) -> Result<Status> {
+    let format = args.options.get("format").map(|s| s.as_str()).unwrap_or("dot");
+    if format != "dot" && format != "json" {
+        return Err(UsageError(format!("Invalid format \"{format}\", expected dot or json")).into());
+    }
+
+    let plando = match load_plando_with_spoiler(args, data).await? {
+        Ok(plando) => plando,
+        Err(status) => return Ok(status)
+    };
+
+    let graph = plando.get_progression_graph().ok_or(anyhow!("Could not generate spoiler data"))?;
+    let graph_str = match format {
+        "json" => graph.to_json()?,
+        _ => graph.to_dot()
+    };
+
+    write_output(args, graph_str.as_bytes())?;
+    Ok(Status::Success)
+}
+
+fn diff(args: &Args, data: &CliData) -> Result<Status> {
+    let other_path = args.get_required_path("other")?;
+    let seed_data = SeedData::from_file(&args.seed_path, &data.game_data, &data.preset_data)?;
+    let other_data = SeedData::from_file(&other_path, &data.game_data, &data.preset_data)?;
+
+    let entries = seed_data.diff(&other_data)?;
+    if entries.is_empty() {
+        println!("No differences");
+    }
+    for entry in entries {
+        println!("{}", entry.to_string(&data.game_data));
+    }
+
+    Ok(Status::Success)
+}
+
+fn merge(args: &Args, data: &CliData) -> Result<Status> {
+    let ours_path = args.get_required_path("ours")?;
+    let theirs_path = args.get_required_path("theirs")?;
+    let out_path = args.get_required_path("out")?;
+
+    let base = SeedData::from_file(&args.seed_path, &data.game_data, &data.preset_data)?;
+    let ours = SeedData::from_file(&ours_path, &data.game_data, &data.preset_data)?;
+    let theirs = SeedData::from_file(&theirs_path, &data.game_data, &data.preset_data)?;
+
+    let merge = merge_seeds(&base, &ours, &theirs, &data.game_data)?;
+    let mut status = Status::Success;
+    for conflict in &merge.conflicts {
+        eprintln!("conflict: {}", conflict.to_string(&data.game_data));
+        status = Status::Invalid;
+    }
+
+    // Validate the merged map, independent changes to different rooms can still be incompatible
+    let mut plando = load_plando_from_seed(data, merge.seed_data)?;
+    plando.map_editor.is_valid_full(&plando.locked_doors);
+    for err in &plando.map_editor.error_list {
+        if err.is_severe() {
+            eprintln!("error: {}", err.to_string(&data.game_data));
+            status = Status::Invalid;
+        } else {
+            eprintln!("warning: {}", err.to_string(&data.game_data));
+        }
+    }
+
+    SeedData::from_plando(&plando).save_to_file(&out_path, &data.game_data)
+        .map_err(|err| UsageError(format!("Could not write {}: {err}", out_path.display())))?;
+    Ok(status)
+}
+
+async fn run() -> Status {
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            return Status::Usage;
+        }
+    };
+    if !["validate", "spoiler", "patch", "difficulty", "graph", "diff", "merge"].contains(&args.command.as_str()) {
+        eprintln!("Unknown command \"{}\"\n\n{USAGE}", args.command);
+        return Status::Usage;
+    }
+
+    let data_dir = args.get_path("data").unwrap_or(PathBuf::from("./data/maprando-data/"));
+    let data = match load_data(&data_dir) {
+        Ok(data) => data,
+        Err(err) => {
+            eprintln!("Failed to load game data: {err}");
+            return Status::LoadFailed;
+        }
+    };
+
+    let res = match args.command.as_str() {
+        "validate" => validate(&args, &data),
+        "spoiler" => spoiler(&args, &data).await,
+        "patch" => patch(&args, &data).await,
+        "difficulty" => difficulty(&args, &data).await,
+        "graph" => graph(&args, &data).await,
+        "diff" => diff(&args, &data),
+        "merge" => merge(&args, &data),
+        _ => unreachable!()
+    };
+
+    match res {
+        Ok(status) => status,
+        Err(err) if err.is::<UsageError>() => {
+            eprintln!("{err}\n\n{USAGE}");
+            Status::Usage
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            Status::LoadFailed
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    ExitCode::from(run().await as u8)
+}
@@ -7,7 +7,7 @@ use askama::Template;
 use log::info;
 use maprando::{customize::{ControllerButton, ControllerConfig, CustomizeSettings, DoorTheme, FlashingSetting, MapTheme, MusicSettings, PaletteTheme, ShakingSetting, StatuesHallwayAudio, StatuesHallwayTiling, TileTheme, mosaic::MosaicTheme, parse_controller_button, samus_sprite::SamusSpriteCategory}, difficulty::{get_full_global, get_link_difficulty_length}, patch::Rom, preset::PresetData, randomize::Randomization, settings::{AreaAssignmentBaseOrder, AreaAssignmentPreset, CrashFixesPreset, DisableETankSetting, DoorLocksSize, ETankRefill, EnemyDrops, Fanfares, ItemMarkers, MapStationReveal, MotherBrainFight, ObjectiveSetting, RandomizerSettings, SpeedBooster, WallJump, get_objective_groups, try_upgrade_settings}, spoiler_map};
 use maprando_game::GameData;
-use maprando_plando_backend::{seed_data::SeedData, Plando};
+use maprando_plando_backend::{analysis::TechReport, seed_data::SeedData, spoiler_export::{SpoilerExportFormat, SpoilerReport}, Plando};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -37,7 +37,9 @@ struct UploadForm {
     allow_spoiler: Option<Text<String>>,
     allow_download: Option<Text<String>>,
     #[multipart(limit = "256KB")]
-    file: form::bytes::Bytes
+    file: Option<form::bytes::Bytes>,
+    #[multipart(limit = "256KB")]
+    code: Option<Text<String>>
 }
 
 #[derive(Serialize, Deserialize)]
@@ -49,7 +51,9 @@ struct FileRandomization {
     allow_download: bool,
     randomization: Randomization,
     settings: RandomizerSettings,
-    logical: bool
+    logical: bool,
+    #[serde(default)]
+    tech_report: Option<TechReport>
 }
 
 #[post("/upload-seed")]
@@ -61,7 +65,14 @@ async fn upload_seed(data: web::Data<AppData>, MultipartForm(form): MultipartFor
         return Err(ErrorBadRequest("Description too long. Maximum of 600 characters."));
     }
 
-    info!("Received seed: {} ({} bytes)", form.name.0, form.file.data.len());
+    // A seed code can be pasted in place of uploading the JSON file
+    let code = form.code.map(|code| code.0).filter(|code| !code.trim().is_empty());
+    let file = form.file.filter(|file| !file.data.is_empty());
+    match (&code, &file) {
+        (Some(code), _) => info!("Received seed code: {} ({} characters)", form.name.0, code.len()),
+        (None, Some(file)) => info!("Received seed: {} ({} bytes)", form.name.0, file.data.len()),
+        (None, None) => return Err(ErrorBadRequest("Missing seed file or seed code"))
+    }
 
     let mut seed_id;
     loop {
@@ -73,9 +84,15 @@ async fn upload_seed(data: web::Data<AppData>, MultipartForm(form): MultipartFor
     }
 
     info!("Parsing seed data");
-    let seed_data = SeedData::from_bytes(form.file.data.to_vec(), &data.game_data, &data.preset_data).map_err(
-        |err| ErrorBadRequest(format!("Could not parse file: {err}"))
-    )?;
+    let seed_data = match (code, file) {
+        (Some(code), _) => SeedData::from_share_code(&code, &data.game_data, &data.preset_data).map_err(
+            |err| ErrorBadRequest(format!("Could not parse seed code: {err}"))
+        )?,
+        (None, Some(file)) => SeedData::from_bytes(file.data.to_vec(), &data.game_data, &data.preset_data).map_err(
+            |err| ErrorBadRequest(format!("Could not parse file: {err}"))
+        )?,
+        (None, None) => unreachable!()
+    };
     info!("Seed data parsed, creator: {}", seed_data.creator_name);
 
     info!("Constructing plando instance");
@@ -102,6 +119,9 @@ async fn upload_seed(data: web::Data<AppData>, MultipartForm(form): MultipartFor
         |err| ErrorBadRequest(format!("Failed to update spoiler data: {err}"))
     )?;
 
+    info!("Collecting used tech");
+    let tech_report = plando.get_tech_report();
+
     info!("Retrieving Spoiler Log");
     let mutex = plando.get_randomization();
     let (r, s) = mutex.as_ref().ok_or_else(
@@ -112,13 +132,14 @@ async fn upload_seed(data: web::Data<AppData>, MultipartForm(form): MultipartFor
     let mb_clearable = s.details.iter().any(|summary| {
         summary.flags.iter().any(|flag| flag.flag == *mb_flag_str)
     });
-    let logically_clearable = mb_clearable && plando.spoiler_overrides.is_empty();
+    let logically_clearable = mb_clearable && plando.spoiler_overrides.is_empty() && plando.spoiler_flag_overrides.is_empty();
 
     let r_json = serde_json::to_value(r)?;
     let mut s_json = serde_json::to_value(s)?;
     let s_json_map = s_json.as_object_mut().unwrap();
     s_json_map.retain(|k, _| k != "forward_traversal" && k != "reverse_traversal");
     s_json_map.insert("spoiler_overrides".to_string(), serde_json::to_value(&plando.spoiler_overrides)?);
+    s_json_map.insert("spoiler_flag_overrides".to_string(), serde_json::to_value(&plando.spoiler_flag_overrides)?);
 
     let desc = if form.desc.0.is_empty() {
         None
@@ -137,10 +158,11 @@ async fn upload_seed(data: web::Data<AppData>, MultipartForm(form): MultipartFor
         "allow_download": allow_download,
         "settings": plando.randomizer_settings,
         "randomization": r_json,
-        "logical": logically_clearable
+        "logical": logically_clearable,
+        "tech_report": tech_report
     }).to_string();
 
-    let seed_data_str = serde_json::to_string_pretty(&seed_data.to_json().map_err(
+    let seed_data_str = serde_json::to_string_pretty(&seed_data.to_json(&data.game_data).map_err(
         |_| ErrorInternalServerError(format!("Failed to parse seed data back into JSON"))
     )?).map_err(
         |_| ErrorInternalServerError(format!("Failed to parse seed data into a JSON string"))
@@ -168,6 +190,13 @@ async fn upload_seed(data: web::Data<AppData>, MultipartForm(form): MultipartFor
         name: format!("{prefix}spoiler.json"),
         data: s_json.to_string().as_bytes().to_vec()
     });
+    let spoiler_report = SpoilerReport::new(&data.game_data, plando.map(), s, &plando.spoiler_overrides, &plando.spoiler_flag_overrides);
+    for format in [SpoilerExportFormat::Text, SpoilerExportFormat::Markdown, SpoilerExportFormat::Csv] {
+        seed.files.push(SeedFile {
+            name: format!("{prefix}spoiler.{}", format.extension()),
+            data: spoiler_report.export(format).into_bytes()
+        });
+    }
     seed.files.push(SeedFile {
         name: format!("{prefix}map-explored.png"),
         data: spoiler_map.explored
@@ -249,7 +278,8 @@ struct SeedTemplate<'a> {
     disableable_etanks: String,
     enemy_drops: String,
     crash_fix_preset: String,
-    objective_names: Vec<String>
+    objective_names: Vec<String>,
+    tech_report: Option<TechReport>
 }
 
 impl<'a> SeedTemplate<'a> {
@@ -446,7 +476,8 @@ async fn get_seed(data: web::Data<AppData>, seed_id: web::Path<String>) -> Resul
         disableable_etanks,
         enemy_drops,
         crash_fix_preset,
-        objective_names
+        objective_names,
+        tech_report: r_data.tech_report
     };
     let render = template.render().map_err(
         |err| ErrorInternalServerError(format!("Error rendering template: {err}"))
@@ -39,6 +39,7 @@ pub enum SidebarPanel {
     Rooms,
     Areas,
     Errors,
+    Tech,
     Benchmark
 }
 
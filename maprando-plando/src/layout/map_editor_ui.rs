@@ -1,6 +1,5 @@
-use maprando::randomize::LockedDoor;
 use maprando_game::{GameData, Map};
-use maprando_plando_backend::map_editor::MapEditor;
+use maprando_plando_backend::{history::Command, Plando};
 use sfml::{graphics::IntRect, system::Vector2i, window::Key};
 
 use crate::utils;
@@ -18,7 +17,12 @@ pub struct MapEditorUi {
 }
 
 impl MapEditorUi {
-    pub fn start_drag(&mut self, map: &Map, room_idx_opt: Option<usize>, mouse_tile_x: usize, mouse_tile_y: usize, game_data: &GameData) {
+    pub fn start_drag(&mut self, plando: &mut Plando, room_idx_opt: Option<usize>, mouse_tile_x: usize, mouse_tile_y: usize) {
+        // The release of the previous drag can get lost, e.g. when the window loses focus. Finish it before starting a new one
+        self.finish_drag(plando);
+
+        let map = plando.map();
+        let game_data = &plando.game_data;
         if let Some(room_idx) = room_idx_opt && map.room_mask[room_idx] {
             if self.selected_room_idx.contains(&room_idx) {
                 // User starts dragging on one of the selected rooms, start dragging all of them
@@ -38,6 +42,8 @@ impl MapEditorUi {
                 self.dragged_room_xoffset = mouse_tile_x - bbox.left as usize;
                 self.dragged_room_yoffset = mouse_tile_y - bbox.top as usize;
             }
+            // The whole drag is recorded as a single command which is finished in stop_drag
+            plando.begin_command(Command::MoveRooms);
         } else {
             // No room is being dragged, start a selection
             if !(Key::LControl.is_pressed() || Key::RControl.is_pressed()) {
@@ -47,13 +53,24 @@ impl MapEditorUi {
         }
     }
 
-    pub fn stop_drag(&mut self, map_editor: &mut MapEditor, mouse_tile_x: usize, mouse_tile_y: usize, game_data: &GameData, locked_doors: &Vec<LockedDoor>) {
+    /// Snaps the dragged rooms into place and finishes the command started in start_drag. Does nothing if no rooms are dragged
+    pub fn finish_drag(&mut self, plando: &mut Plando) {
+        if self.dragged_room_idx.is_empty() {
+            return;
+        }
+        for i in 0..self.dragged_room_idx.len() {
+            plando.snap_room(self.dragged_room_idx[i]);
+        }
+        // Fix any potential issues that may come up such as syncing up door locks
+        plando.sync_doors();
+        plando.end_command();
+        self.selected_room_idx.append(&mut self.dragged_room_idx);
+    }
+
+    pub fn stop_drag(&mut self, plando: &mut Plando, mouse_tile_x: usize, mouse_tile_y: usize) {
         if !self.dragged_room_idx.is_empty() {
             // If we are dragging rooms, snap them into place
-            for i in 0..self.dragged_room_idx.len() {
-                map_editor.snap_room(self.dragged_room_idx[i], locked_doors);
-            }
-            self.selected_room_idx.append(&mut self.dragged_room_idx);
+            self.finish_drag(plando);
         } else if self.selection_start.is_some() && (self.selected_room_idx.is_empty() || Key::LControl.is_pressed() || Key::RControl.is_pressed()) {
             // Otherwise we finish a selection
             let sel_pos = self.selection_start.unwrap();
@@ -62,12 +79,12 @@ impl MapEditorUi {
             let rect = IntRect::new(sel_pos.x, sel_pos.y,w, h);
             let rect = utils::normalize_rect(rect);
 
-            for (room_idx, &(room_x, room_y)) in map_editor.get_map().rooms.iter().enumerate() {
-                if !map_editor.get_map().room_mask[room_idx] {
+            for (room_idx, &(room_x, room_y)) in plando.map().rooms.iter().enumerate() {
+                if !plando.map().room_mask[room_idx] {
                     continue;
                 }
 
-                let room_geometry = &game_data.room_geometry[room_idx];
+                let room_geometry = &plando.game_data.room_geometry[room_idx];
                 let room_width = room_geometry.map[0].len();
                 let room_height = room_geometry.map.len();
                 let room_rect = IntRect::new(room_x as i32, room_y as i32, room_width as i32, room_height as i32);
@@ -109,8 +126,8 @@ impl MapEditorUi {
         })
     }
 
-    pub fn move_dragged_rooms(&mut self, map_editor: &mut MapEditor, mouse_tile_x: usize, mouse_tile_y: usize, game_data: &GameData) -> bool {
-        if let Some(bbox) = self.get_dragged_bbox(map_editor.get_map(), game_data) {
+    pub fn move_dragged_rooms(&mut self, plando: &mut Plando, mouse_tile_x: usize, mouse_tile_y: usize) -> bool {
+        if let Some(bbox) = self.get_dragged_bbox(plando.map(), &plando.game_data) {
             let left = mouse_tile_x as i32 - self.dragged_room_xoffset as i32;
             let top = mouse_tile_y as i32 - self.dragged_room_yoffset as i32;
 
@@ -123,14 +140,14 @@ impl MapEditorUi {
             }
 
             for &drag_idx in &self.dragged_room_idx {
-                let (room_x, room_y) = map_editor.get_map().rooms[drag_idx];
+                let (room_x, room_y) = plando.map().rooms[drag_idx];
                 let x_offset = room_x as i32 - bbox.left;
                 let y_offset = room_y as i32 - bbox.top;
 
                 let new_x = (left + x_offset) as usize;
                 let new_y = (top + y_offset) as usize;
 
-                map_editor.move_room(drag_idx, new_x, new_y);
+                plando.move_room(drag_idx, new_x, new_y);
             }
             return true;
         }
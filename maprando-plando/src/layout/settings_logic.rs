@@ -3,7 +3,7 @@ use std::{io::Write, path::Path};
 use anyhow::Result;
 use egui::{CollapsingHeader, Context, SelectableLabel};
 use hashbrown::HashMap;
-use maprando::{preset::PresetData, settings::{CrashFixes, CrashFixesPreset, DisableETankSetting, DoorLocksSize, ETankRefill, EnemyDrops, EnhancedMapLevel, EnhancedMapOther, EnhancedMapSettings, EnhancedMapWalls, Fanfares, FixMode, InitialMapRevealSettings, ItemCount, ItemMarkers, MapRevealLevel, MapStationReveal, MotherBrainFight, ObjectiveScreen, ObjectiveSetting, RandomizerSettings, SaveAnimals, SpeedBooster, WallJump}};
+use maprando::{preset::PresetData, settings::{CrashFixes, CrashFixesPreset, DisableETankSetting, DoorLocksSize, ETankRefill, EnemyDrops, EnhancedMapLevel, EnhancedMapOther, EnhancedMapSettings, EnhancedMapWalls, Fanfares, FixMode, InitialMapRevealSettings, ItemCount, ItemMarkers, MapRevealLevel, MapStationReveal, MotherBrainFight, Objective, ObjectiveScreen, ObjectiveSetting, RandomizerSettings, SaveAnimals, SpeedBooster, WallJump}};
 use maprando_game::Item;
 use maprando_plando_backend::{ITEM_VALUES, Placeable};
 use strum_macros::VariantArray;
@@ -28,6 +28,19 @@ pub struct LogicCustomization {
     pub use_custom_escape_time: bool,
     pub custom_escape_time: usize,
     pub creator_name: String,
+
+    pub use_custom_objectives: bool,
+    pub custom_objectives: Vec<Objective>, // Objectives picked by the author in the order they were picked
+
+    pub difficulty_analysis: Option<String>, // Result of the last minimum difficulty analysis
+    pub difficulty_analysis_running: bool,
+    pub difficulty_analysis_requested: bool,
+
+    pub compare_presets: [usize; 2], // Skill preset idxs to compare
+    pub preset_comparison: Option<String>, // Result of the last skill preset comparison
+    pub preset_comparison_running: bool,
+    pub preset_comparison_requested: bool,
+    pub preset_comparison_clear_requested: bool,
 }
 
 impl LogicCustomization {
@@ -42,19 +55,36 @@ impl LogicCustomization {
             settings,
             use_custom_escape_time: false,
             custom_escape_time: 0,
-            creator_name: "Plando".to_string()
+            creator_name: "Plando".to_string(),
+            use_custom_objectives: false,
+            custom_objectives: Vec::new(),
+            difficulty_analysis: None,
+            difficulty_analysis_running: false,
+            difficulty_analysis_requested: false,
+            compare_presets: [0, 1],
+            preset_comparison: None,
+            preset_comparison_running: false,
+            preset_comparison_requested: false,
+            preset_comparison_clear_requested: false
         };
         res.init_starting_items();
         res
     }
 
-    pub fn load(&mut self, settings: RandomizerSettings, custom_escape_time: Option<usize>, creator_name: String) {
+    pub fn load(&mut self, settings: RandomizerSettings, custom_escape_time: Option<usize>, creator_name: String, custom_objectives: Option<Vec<Objective>>) {
         self.settings = settings.clone();
         self.cur_settings = settings;
         self.init_starting_items();
         self.custom_escape_time = custom_escape_time.unwrap_or(0);
         self.use_custom_escape_time = custom_escape_time.is_some();
         self.creator_name = creator_name;
+        self.use_custom_objectives = custom_objectives.is_some();
+        self.custom_objectives = custom_objectives.unwrap_or_default();
+    }
+
+    /// Objectives to apply to the plando, None if they should be rolled from the objective settings
+    pub fn get_custom_objectives(&self) -> Option<Vec<Objective>> {
+        if self.use_custom_objectives { Some(self.custom_objectives.clone()) } else { None }
     }
 
     fn init_starting_items(&mut self) {
@@ -237,6 +267,55 @@ impl LogicCustomization {
                 // Major glitches
                 self.cur_settings.other_settings.enable_major_glitches.generate("Enable major glitches", ui);
             });
+            // Minimum difficulty of the current seed
+            ui.collapsing("Minimum Difficulty", |ui| {
+                ui.horizontal(|ui| {
+                    let btn = egui::Button::new("Analyze current Seed");
+                    if ui.add_enabled(!self.difficulty_analysis_running, btn).on_hover_text("Finds the lowest skill preset on which the applied settings are beatable").clicked() {
+                        self.difficulty_analysis_requested = true;
+                    }
+                    if self.difficulty_analysis_running {
+                        ui.spinner();
+                    }
+                });
+                if let Some(analysis) = &self.difficulty_analysis {
+                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                        ui.label(analysis);
+                    });
+                }
+            });
+            // Comparison of the current seed on two skill presets
+            ui.collapsing("Compare Skill Presets", |ui| {
+                ui.horizontal(|ui| {
+                    for (i, preset_idx) in self.compare_presets.iter_mut().enumerate() {
+                        let selected_text = self.preset_data.skill_presets.get(*preset_idx).and_then(|x| x.preset.clone()).unwrap_or_default();
+                        egui::ComboBox::from_id_salt(format!("combo_compare_preset_{i}")).selected_text(selected_text).show_ui(ui, |ui| {
+                            for (idx, preset) in self.preset_data.skill_presets.iter().enumerate() {
+                                ui.selectable_value(preset_idx, idx, preset.preset.clone().unwrap_or_default());
+                            }
+                        });
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let btn = egui::Button::new("Compare");
+                    if ui.add_enabled(!self.preset_comparison_running, btn).on_hover_text("Lists the items whose step, reachability or tech changes between the two presets and highlights them on the map").clicked() {
+                        self.preset_comparison_requested = true;
+                    }
+                    if ui.add_enabled(self.preset_comparison.is_some(), egui::Button::new("Clear")).clicked() {
+                        self.preset_comparison = None;
+                        self.preset_comparison_clear_requested = true;
+                    }
+                    if self.preset_comparison_running {
+                        ui.spinner();
+                    }
+                });
+                if let Some(comparison) = &self.preset_comparison {
+                    egui::ScrollArea::vertical().id_salt("scroll_preset_comparison").max_height(200.0).show(ui, |ui| {
+                        ui.label(comparison);
+                    });
+                }
+            });
+
             // Save preset
             ui.horizontal(|ui| {
                 ui.label("Save preset as");
@@ -620,15 +699,30 @@ impl LogicCustomization {
         let obj_map: HashMap<String, usize> = self.cur_settings.objective_settings.objective_options.iter().enumerate().map(
             |(i, x)| (format!("{:?}", x.objective).to_string(), i)
         ).collect();
+        ui.checkbox(&mut self.use_custom_objectives, "Pick exact objectives").on_hover_text_at_pointer(
+            "Use exactly the picked objectives instead of rolling them from the objective settings"
+        );
+        ui.separator();
         for group in obj_groups {
             ui.label(&group.name);
             egui::Grid::new(format!("grid_obj_{}", group.name)).num_columns(4).show(ui, |ui| {
                 for (obj_internal, obj_display) in group.objectives {
                     let idx = obj_map[&obj_internal];
-                    let obj = &mut self.cur_settings.objective_settings.objective_options[idx].setting;
                     ui.label(obj_display);
-                    ui.selectable_value(obj, ObjectiveSetting::No, "No");
-                    ui.selectable_value(obj, ObjectiveSetting::Yes, "Yes");
+                    if self.use_custom_objectives {
+                        let objective = self.cur_settings.objective_settings.objective_options[idx].objective.clone();
+                        let pos = self.custom_objectives.iter().position(|x| *x == objective);
+                        if ui.selectable_label(pos.is_none(), "No").clicked() {
+                            self.custom_objectives.retain(|x| *x != objective);
+                        }
+                        if ui.selectable_label(pos.is_some(), "Yes").clicked() && pos.is_none() {
+                            self.custom_objectives.push(objective);
+                        }
+                    } else {
+                        let obj = &mut self.cur_settings.objective_settings.objective_options[idx].setting;
+                        ui.selectable_value(obj, ObjectiveSetting::No, "No");
+                        ui.selectable_value(obj, ObjectiveSetting::Yes, "Yes");
+                    }
                     ui.end_row();
                 }
             });
@@ -6,7 +6,7 @@ use hashbrown::{HashMap, HashSet};
 use input_state::MouseState;
 use maprando::{customize::CustomizeSettings, difficulty::{get_full_global, get_link_difficulty_length}, map_repository::MapRepository, patch::Rom, preset::PresetData, settings::{Objective, RandomizerSettings, try_upgrade_settings}, spoiler_log::SpoilerRouteEntry};
 use maprando_game::{BeamType, DoorType, GameData, Item, Map, MapTileEdge, MapTileInterior, MapTileSpecialType};
-use maprando_plando_backend::{get_double_item_offset, map_editor::{self, MapEditor, MapErrorType}, randomize::get_vertex_info, seed_data::SeedData, DoubleItemPlacement, Placeable, Plando, SpoilerOverride, ITEM_VALUES};
+use maprando_plando_backend::{analysis::{DifficultyAnalysis, ItemRequirement, PresetComparison, RequiredItemsAnalysis, TechReport}, get_double_item_offset, history::Command, logic::{LogicTiming, ReachabilityResult}, map_editor::{self, MapEditor, MapErrorType}, randomize::{get_vertex_info, get_vertex_info_by_id}, seed_data::SeedData, seed_merge::merge_seeds, spoiler_export::SpoilerExportFormat, DoubleItemPlacement, OverrideTarget, Placeable, Plando, SpoilerFlagOverride, SpoilerOverride, ITEM_VALUES};
 use rand::{rngs::StdRng, RngCore, SeedableRng};
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
@@ -15,7 +15,7 @@ use sfml::{
         cpp::FBox, graphics::{
             self, Color, FloatRect, IntRect, PrimitiveType, RenderStates, RenderTarget, RenderTexture, RenderWindow, Shape, Transformable, Vertex
         }, system::{Vector2f, Vector2i}, window::{
-            mouse, ContextSettings, Event, Key, Style
+            clipboard, mouse, ContextSettings, Event, Key, Style
         }
     };
 use strum::VariantArray;
@@ -679,6 +679,15 @@ fn generate_door_sprites() -> Result<FBox<graphics::Image>> {
     Ok(img_doors)
 }
 
+/// Red: Required, Yellow: Soft-required, Green: Optional
+fn get_requirement_color(requirement: ItemRequirement) -> Color {
+    match requirement {
+        ItemRequirement::Required => Color::RED,
+        ItemRequirement::SoftRequired => Color::YELLOW,
+        ItemRequirement::Optional => Color::GREEN
+    }
+}
+
 fn draw_thick_line_strip(rt: &mut dyn RenderTarget, states: &RenderStates, strip: &[Vertex], thickness: f32) {
     for i in 1..strip.len() {
         let prev = &strip[i - 1];
@@ -807,8 +816,8 @@ enum Hotkeys {
     ToggleAutoSpoiler,
     EraseSelection,
     SelectAll,
-    //Undo,
-    //Redo
+    Undo,
+    Redo
 }
 
 impl Hotkeys {
@@ -821,9 +830,9 @@ impl Hotkeys {
             Hotkeys::OpenSpoilerOverride => Keybind::new(id, "Open Spoiler Overrides", "Opens the Spoiler Overrides window for the current step", vec![Key::F6]),
             Hotkeys::ToggleAutoSpoiler => Keybind::new(id, "Toggle Auto-Spoiler", "Toggles the automatic spoiler update setting", vec![Key::F7]),
             Hotkeys::EraseSelection => Keybind::new(id, "Erase selected rooms", "Erases the selected rooms from the map", vec![Key::Delete]),
-            Hotkeys::SelectAll => Keybind::new(id, "Select all rooms", "Adds all placed rooms to the selection", vec![Key::LControl, Key::A])
-            //Hotkeys::Undo => Keybind::new(id, "Undo", "Undoes the last action", vec![Key::LControl, Key::Z]),
-            //Hotkeys::Redo => Keybind::new(id, "Redo", "Redoes the last action", vec![Key::LControl, Key::Y]),
+            Hotkeys::SelectAll => Keybind::new(id, "Select all rooms", "Adds all placed rooms to the selection", vec![Key::LControl, Key::A]),
+            Hotkeys::Undo => Keybind::new(id, "Undo", "Undoes the last action", vec![Key::LControl, Key::Z]),
+            Hotkeys::Redo => Keybind::new(id, "Redo", "Redoes the last action", vec![Key::LControl, Key::Y]),
         }
     }
 }
@@ -899,6 +908,13 @@ struct PlandoApp {
     is_mouse_public: bool,
 
     spoiler_step: usize,
+    tech_report: Option<TechReport>,
+    seed_report: Option<(String, Vec<String>)>, // Title, lines of a seed comparison or merge
+    route_query_nodes: Vec<(usize, usize)>, // Shift-clicked start and target (RoomId, NodeId) of a reachability query
+    route_query: Option<ReachabilityResult>,
+    preset_comparison: Option<PresetComparison>, // Items which differ between two skill presets are highlighted on the map
+    preset_comparison_edit_count: usize, // Edit count of the seed the preset comparison was started for
+    required_items: Option<RequiredItemsAnalysis>, // Shown as icons next to the analyzed items
     spoiler_type: SpoilerTypeTracker,
     modal_type: ModalType,
     override_window: Option<usize>,
@@ -917,8 +933,13 @@ struct PlandoApp {
     settings_open: bool,
 
     handle_spoiler: Option<JoinHandle<Result<()>>>,
+    handle_fill: Option<JoinHandle<Result<Vec<Item>>>>,
+    handle_difficulty: Option<JoinHandle<Result<DifficultyAnalysis>>>,
+    handle_compare: Option<JoinHandle<Result<PresetComparison>>>,
+    handle_required: Option<JoinHandle<Result<RequiredItemsAnalysis>>>,
     handle_patch: Option<(JoinHandle<Result<Rom>>, String)>,
     handle_map_download: Option<JoinHandle<Result<()>>>,
+    handle_route: Option<JoinHandle<Result<ReachabilityResult>>>,
 
     global_timer: u64
 }
@@ -1107,6 +1128,13 @@ impl PlandoApp {
             is_mouse_public: true,
 
             spoiler_step: 0,
+            tech_report: None,
+            seed_report: None,
+            route_query_nodes: Vec::new(),
+            route_query: None,
+            preset_comparison: None,
+            preset_comparison_edit_count: 0,
+            required_items: None,
             spoiler_type: SpoilerTypeTracker::new(),
             modal_type: ModalType::None,
             override_window: None,
@@ -1125,8 +1153,13 @@ impl PlandoApp {
             settings_open: false,
 
             handle_spoiler: None,
+            handle_fill: None,
+            handle_difficulty: None,
+            handle_compare: None,
+            handle_required: None,
             handle_patch: None,
             handle_map_download: None,
+            handle_route: None,
 
             global_timer: 0
         };
@@ -1156,13 +1189,90 @@ impl PlandoApp {
 
     fn save_seed(&mut self, path: &Path) -> Result<()> {
         let seed_data = SeedData::from_plando(&self.plando);
-        seed_data.save_to_file(path)?;
+        seed_data.save_to_file(path, &self.plando.game_data)?;
 
         self.push_recent_seed(path.to_str().unwrap().to_string());
 
         Ok(())
     }
 
+    fn export_progression_graph(&self, path: &Path) -> Result<()> {
+        let graph = self.plando.get_progression_graph().ok_or(anyhow!("No spoiler data available, update the spoiler log first"))?;
+        let graph_str = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => graph.to_json()?,
+            _ => graph.to_dot()
+        };
+        std::fs::write(path, graph_str)?;
+        Ok(())
+    }
+
+    fn export_spoiler(&self, path: &Path, format: SpoilerExportFormat) -> Result<()> {
+        let report = self.plando.get_spoiler_report().ok_or(anyhow!("No spoiler data available, update the spoiler log first"))?;
+        std::fs::write(path, report.export(format))?;
+        Ok(())
+    }
+
+    fn compare_with_file(&mut self, path: &Path) -> Result<()> {
+        let other = SeedData::from_file(path, &self.plando.game_data, &self.logic_customization.preset_data)?;
+        let seed_data = SeedData::from_plando(&self.plando);
+        let entries = seed_data.diff(&other)?;
+
+        let name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+        let mut lines: Vec<String> = entries.iter().map(|entry| entry.to_string(&self.plando.game_data)).collect();
+        if lines.is_empty() {
+            lines.push("No differences".to_string());
+        }
+        self.seed_report = Some((format!("Changes from current Seed to {name}"), lines));
+
+        Ok(())
+    }
+
+    fn merge_with_files(&mut self, base_path: &Path, theirs_path: &Path) -> Result<()> {
+        let base = SeedData::from_file(base_path, &self.plando.game_data, &self.logic_customization.preset_data)?;
+        let theirs = SeedData::from_file(theirs_path, &self.plando.game_data, &self.logic_customization.preset_data)?;
+        let ours = SeedData::from_plando(&self.plando);
+
+        let merge = merge_seeds(&base, &ours, &theirs, &self.plando.game_data)?;
+        let mut lines: Vec<String> = merge.conflicts.iter().map(|conflict| conflict.to_string(&self.plando.game_data)).collect();
+        if lines.is_empty() {
+            lines.push("No conflicts".to_string());
+        }
+
+        self.plando.begin_command(Command::MergeSeeds);
+        let res = merge.seed_data.load_into_plando(&mut self.plando);
+        self.plando.end_command();
+        res?;
+        self.plando.map_editor.is_valid_full(&self.plando.locked_doors);
+
+        let name = theirs_path.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+        self.seed_report = Some((format!("Conflicts merging {name}"), lines));
+
+        if self.settings.spoiler_auto_update {
+            if let Err(err) = self.update_spoiler_data_async(self.settings.rebuild_steps) {
+                self.modal_type = ModalType::Error(format!("Could not update the spoiler log after merging: {err}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn copy_seed_code(&self) -> Result<()> {
+        let code = SeedData::from_plando(&self.plando).to_share_code(&self.plando.game_data)?;
+        clipboard::set_string(code.as_str());
+        Ok(())
+    }
+
+    fn paste_seed_code(&mut self) -> Result<()> {
+        let code = clipboard::get_string();
+        let seed_data = SeedData::from_share_code(&code, &self.plando.game_data, &self.logic_customization.preset_data)?;
+
+        seed_data.load_into_plando(&mut self.plando)?;
+
+        self.update_spoiler_data_async(self.settings.rebuild_steps)?;
+
+        Ok(())
+    }
+
     fn load_seed(&mut self, path: &Path) -> Result<()> {
         let seed_data = SeedData::from_file(path, &self.plando.game_data, &self.logic_customization.preset_data)?;
 
@@ -1212,6 +1322,59 @@ impl PlandoApp {
         Ok(())
     }
 
+    /// Shift-clicked nodes are the start and target of a reachability query with the items and flags collected before the current spoiler step.
+    /// A third click starts a new query
+    fn select_route_node(&mut self, node: (usize, usize)) {
+        if let Some(handle) = self.handle_route.take() {
+            handle.abort();
+        }
+        if self.route_query_nodes.len() >= 2 {
+            self.route_query_nodes.clear();
+        }
+        self.route_query = None;
+        self.route_query_nodes.push(node);
+
+        if self.route_query_nodes.len() == 2 {
+            let (items, flags) = self.plando.get_collected_until_step(self.spoiler_step);
+            match self.plando.query_reachability(self.route_query_nodes[0], self.route_query_nodes[1], &items, &flags) {
+                Ok(handle) => self.handle_route = Some(handle),
+                Err(err) => self.modal_type = ModalType::Error(err.to_string())
+            }
+        }
+        self.schedule_redraw();
+    }
+
+    fn clear_route_query(&mut self) {
+        if let Some(handle) = self.handle_route.take() {
+            handle.abort();
+        }
+        self.route_query_nodes.clear();
+        self.route_query = None;
+        self.schedule_redraw();
+    }
+
+    fn fill_item_locations_async(&mut self, area: Option<usize>) -> Result<()> {
+        if let Some(handle) = &self.handle_fill {
+            handle.abort();
+        }
+
+        let item_pool = self.plando.item_pool.clone().unwrap_or_else(|| self.plando.get_default_item_pool());
+        self.handle_fill = Some(self.plando.fill_item_locations(&item_pool, area)?);
+
+        Ok(())
+    }
+
+    fn analyze_required_items_async(&mut self) -> Result<()> {
+        if let Some(handle) = self.handle_required.take() {
+            self.plando.cancel_required_items_analysis();
+            handle.abort();
+        }
+
+        self.handle_required = Some(self.plando.analyze_required_items()?);
+
+        Ok(())
+    }
+
     async fn update_handles(&mut self) -> Result<()> {
         if self.handle_spoiler.as_ref().is_some_and(|handle| handle.is_finished()) {
             let handle = self.handle_spoiler.take().unwrap();
@@ -1225,10 +1388,93 @@ impl PlandoApp {
             }
 
             self.plando.check_warnings();
+            self.tech_report = self.plando.get_tech_report();
 
             self.schedule_redraw();
         }
 
+        if self.handle_route.as_ref().is_some_and(|handle| handle.is_finished()) {
+            let handle = self.handle_route.take().unwrap();
+            match handle.await {
+                Ok(res) => {
+                    self.route_query = Some(res?);
+                    self.schedule_redraw();
+                }
+                Err(err) => if err.is_panic() {
+                    bail!(err.to_string())
+                }
+            }
+        }
+
+        if self.handle_fill.as_ref().is_some_and(|handle| handle.is_finished()) {
+            let handle = self.handle_fill.take().unwrap();
+            match handle.await {
+                Ok(res) => {
+                    self.plando.apply_item_fill(res?);
+                    self.schedule_redraw();
+                    if self.settings.spoiler_auto_update {
+                        self.update_spoiler_data_async(self.settings.rebuild_steps)?;
+                    }
+                }
+                Err(err) => if err.is_panic() {
+                    bail!(err.to_string())
+                }
+            }
+        }
+
+        if self.handle_difficulty.as_ref().is_some_and(|handle| handle.is_finished()) {
+            let handle = self.handle_difficulty.take().unwrap();
+            self.logic_customization.difficulty_analysis_running = false;
+            match handle.await {
+                Ok(res) => {
+                    let analysis = res?;
+                    self.logic_customization.difficulty_analysis = Some(analysis.to_string(&self.plando.game_data));
+                }
+                Err(err) => if err.is_panic() {
+                    bail!(err.to_string())
+                }
+            }
+        }
+
+        if self.handle_required.as_ref().is_some_and(|handle| handle.is_finished()) {
+            let handle = self.handle_required.take().unwrap();
+            match handle.await {
+                Ok(res) => {
+                    let analysis = res?;
+                    let lines = analysis.to_string(&self.plando.game_data).lines().map(|x| x.to_string()).collect();
+                    self.seed_report = Some(("Required Items".to_string(), lines));
+                    self.required_items = Some(analysis);
+                    self.schedule_redraw();
+                }
+                Err(err) => if err.is_panic() {
+                    bail!(err.to_string())
+                }
+            }
+        }
+
+        if self.handle_compare.as_ref().is_some_and(|handle| handle.is_finished()) {
+            let handle = self.handle_compare.take().unwrap();
+            self.logic_customization.preset_comparison_running = false;
+            match handle.await {
+                Ok(res) => {
+                    let comparison = res?;
+                    self.logic_customization.preset_comparison = Some(comparison.to_string(&self.plando.game_data));
+                    self.preset_comparison = Some(comparison);
+                    self.schedule_redraw();
+                }
+                Err(err) => if err.is_panic() {
+                    bail!(err.to_string())
+                }
+            }
+        }
+
+        // The comparison no longer matches the items on the map once they or the map were edited
+        if self.preset_comparison.is_some() && self.preset_comparison_edit_count != self.plando.get_edit_count() {
+            self.preset_comparison = None;
+            self.logic_customization.preset_comparison = None;
+            self.schedule_redraw();
+        }
+
         if self.handle_patch.as_ref().is_some_and(|handle| handle.0.is_finished()) {
             let (handle, save_path) = self.handle_patch.take().unwrap();
             let path = Path::new(&save_path);
@@ -1287,6 +1533,19 @@ impl PlandoApp {
         self.should_redraw = true;
     }
 
+    fn on_history_changed(&mut self) {
+        // Rooms may have been removed from the map by undoing or redoing
+        let room_mask = &self.plando.map().room_mask;
+        self.map_editor.selected_room_idx.retain(|&idx| room_mask[idx]);
+        self.schedule_redraw();
+
+        if self.settings.spoiler_auto_update {
+            if let Err(err) = self.update_spoiler_data_async(self.settings.rebuild_steps) {
+                self.modal_type = ModalType::Error(err.to_string());
+            }
+        }
+    }
+
     fn redraw_map(&mut self, tex: &mut RenderTexture) {
         let draw_subareas = self.layout.sidebar_tab == SidebarPanel::Areas;
 
@@ -1495,6 +1754,18 @@ impl PlandoApp {
                         window.set_view(&graphics::View::from_rect(graphics::Rect::new(0.0, 0.0, width as f32, height as f32)).unwrap());
                         self.view.window_size = window.size().as_other();
                     },
+                    Event::LostFocus => {
+                        // The mouse release of a drag is not received outside of the window, so the drag is finished right away
+                        if !self.map_editor.dragged_room_idx.is_empty() {
+                            self.map_editor.finish_drag(&mut self.plando);
+                            self.schedule_redraw();
+                            if self.settings.spoiler_auto_update {
+                                if let Err(err) = self.update_spoiler_data_async(self.settings.rebuild_steps) {
+                                    self.modal_type = ModalType::Error(err.to_string());
+                                }
+                            }
+                        }
+                    },
                     _ => {}
                 }
             }
@@ -1516,7 +1787,9 @@ impl PlandoApp {
                                 self.settings.spoiler_auto_update ^= true;
                             },
                             Hotkeys::EraseSelection => {
+                                self.plando.begin_command(Command::EraseRoom);
                                 self.map_editor.selected_room_idx.iter().for_each(|&idx| self.plando.erase_room(idx));
+                                self.plando.end_command();
                                 self.map_editor.selected_room_idx.clear();
                                 self.redraw_map(&mut tex_base_map);
                             },
@@ -1528,8 +1801,16 @@ impl PlandoApp {
                                     }
                                 }
                             }
-                            //Hotkeys::Undo => println!("Undo"),
-                            //Hotkeys::Redo => println!("Redo"),
+                            Hotkeys::Undo => {
+                                if self.plando.undo().is_some() {
+                                    self.on_history_changed();
+                                }
+                            },
+                            Hotkeys::Redo => {
+                                if self.plando.redo().is_some() {
+                                    self.on_history_changed();
+                                }
+                            }
                         }
                         break;
                     }
@@ -1716,6 +1997,52 @@ impl PlandoApp {
                                 }
                             });
                         });
+                        if ui.button("Copy Seed Code").clicked() {
+                            if let Err(err) = self.copy_seed_code() {
+                                self.modal_type = ModalType::Error(err.to_string());
+                            }
+                            ui.close_menu();
+                        }
+                        if ui.button("Paste Seed Code").clicked() {
+                            if let Err(err) = self.paste_seed_code() {
+                                self.modal_type = ModalType::Error(err.to_string());
+                            }
+                            self.schedule_redraw();
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui.button("Compare with file…").clicked() {
+                            let file_opt = FileDialog::new()
+                                .set_title("Compare current seed with JSON file")
+                                .set_directory("/")
+                                .add_filter("JSON File", &["json"])
+                                .pick_file();
+                            if let Some(file) = file_opt {
+                                if let Err(err) = self.compare_with_file(file.as_path()) {
+                                    self.modal_type = ModalType::Error(err.to_string());
+                                }
+                            }
+                            ui.close_menu();
+                        }
+                        if ui.button("Merge with files…").clicked() {
+                            let base_opt = FileDialog::new()
+                                .set_title("Select the common base seed JSON file")
+                                .set_directory("/")
+                                .add_filter("JSON File", &["json"])
+                                .pick_file();
+                            let theirs_opt = base_opt.as_ref().and_then(|_| FileDialog::new()
+                                .set_title("Select the seed JSON file to merge into the current seed")
+                                .set_directory("/")
+                                .add_filter("JSON File", &["json"])
+                                .pick_file());
+                            if let (Some(base), Some(theirs)) = (base_opt, theirs_opt) {
+                                if let Err(err) = self.merge_with_files(&base, &theirs) {
+                                    self.modal_type = ModalType::Error(err.to_string());
+                                }
+                                self.schedule_redraw();
+                            }
+                            ui.close_menu();
+                        }
                         ui.separator();
                         if ui.button("Upload Seed").clicked() {
                             let tmp_path = Path::new(Upload::TMP_FILE_PATH);
@@ -1725,6 +2052,37 @@ impl PlandoApp {
                             };
                             ui.close_menu();
                         }
+                        if ui.button("Export Progression Graph").clicked() {
+                            let file_opt = FileDialog::new()
+                                .set_title("Export Progression Graph")
+                                .set_directory("/")
+                                .add_filter("DOT File", &["dot"])
+                                .add_filter("JSON File", &["json"])
+                                .save_file();
+                            if let Some(file) = file_opt {
+                                if let Err(err) = self.export_progression_graph(file.as_path()) {
+                                    self.modal_type = ModalType::Error(err.to_string());
+                                }
+                            }
+                            ui.close_menu();
+                        }
+                        ui.menu_button("Export Spoiler as...", |ui| {
+                            for &format in SpoilerExportFormat::VARIANTS {
+                                if ui.button(format.to_string()).clicked() {
+                                    let file_opt = FileDialog::new()
+                                        .set_title("Export Spoiler")
+                                        .set_directory("/")
+                                        .add_filter(format!("{} File", format.to_string()), &[format.extension()])
+                                        .save_file();
+                                    if let Some(file) = file_opt {
+                                        if let Err(err) = self.export_spoiler(file.as_path(), format) {
+                                            self.modal_type = ModalType::Error(err.to_string());
+                                        }
+                                    }
+                                    ui.close_menu();
+                                }
+                            }
+                        });
                         ui.separator();
                         if ui.button("Patch ROM").clicked() {
                             self.settings_customization.open = true;
@@ -1813,11 +2171,13 @@ impl PlandoApp {
                         }
                         ui.separator();
                         if ui.button("Replace Nothings with Missiles").clicked() {
+                            self.plando.begin_command(Command::PlaceItem);
                             for i in 0..self.plando.item_locations.len() {
                                 if self.plando.item_locations[i] == Item::Nothing {
                                     let _ = self.plando.place_item(i, Item::Missile);
                                 }
                             }
+                            self.plando.end_command();
                             if self.settings.spoiler_auto_update {
                                 if let Err(err) = self.update_spoiler_data_async(self.settings.rebuild_steps) {
                                     self.modal_type = ModalType::Error(err.to_string());
@@ -1825,7 +2185,41 @@ impl PlandoApp {
                             }
                             ui.close_menu();
                         }
+                        ui.add_enabled_ui(self.handle_fill.is_none(), |ui| {
+                            ui.menu_button("Auto-fill empty Locations", |ui| {
+                                let mut fill_area = None;
+                                let mut clicked = ui.button("All Areas").clicked();
+                                ui.separator();
+                                let areas = ["Crateria", "Brinstar", "Norfair", "Wrecked Ship", "Maridia", "Tourian"];
+                                for (idx, area) in areas.into_iter().enumerate() {
+                                    if ui.button(format!("Only {area}")).clicked() {
+                                        clicked = true;
+                                        fill_area = Some(idx);
+                                    }
+                                }
+                                if clicked {
+                                    if let Err(err) = self.fill_item_locations_async(fill_area) {
+                                        self.modal_type = ModalType::Error(err.to_string());
+                                    }
+                                    ui.close_menu();
+                                }
+                            });
+                        });
+                        ui.add_enabled_ui(self.handle_required.is_none(), |ui| {
+                            if ui.button("Analyze required Items").on_hover_text("Checks for every placed progression item whether the seed is still beatable without it").clicked() {
+                                if let Err(err) = self.analyze_required_items_async() {
+                                    self.modal_type = ModalType::Error(err.to_string());
+                                }
+                                ui.close_menu();
+                            }
+                        });
+                        if ui.add_enabled(self.required_items.is_some(), egui::Button::new("Hide required Item icons")).clicked() {
+                            self.required_items = None;
+                            self.schedule_redraw();
+                            ui.close_menu();
+                        }
                         if ui.button("Randomize Doors").clicked() {
+                            self.plando.begin_command(Command::PlaceDoor);
                             self.plando.clear_doors();
 
                             let seed = (self.plando.rng.next_u64() & 0xFFFFFFFF) as usize;
@@ -1834,6 +2228,7 @@ impl PlandoApp {
                                 let (room_idx, door_idx) = self.plando.game_data.room_and_door_idxs_by_door_ptr_pair[&door.src_ptr_pair];
                                 let _ = self.plando.place_door(room_idx, door_idx, Some(door.door_type), false);
                             }
+                            self.plando.end_command();
 
                             if self.settings.spoiler_auto_update {
                                 if let Err(err) = self.update_spoiler_data_async(self.settings.rebuild_steps) {
@@ -1842,9 +2237,28 @@ impl PlandoApp {
                             }
                         }
 
+                        ui.menu_button("Target Item Pool", |ui| {
+                            if ui.button("Use Default Item Pool").clicked() {
+                                self.plando.item_pool = Some(self.plando.get_default_item_pool());
+                                self.plando.check_item_pool();
+                                ui.close_menu();
+                            }
+                            if ui.button("Use current Placements").clicked() {
+                                self.plando.set_item_pool_from_placements();
+                                self.plando.check_item_pool();
+                                ui.close_menu();
+                            }
+                            if ui.add_enabled(self.plando.item_pool.is_some(), egui::Button::new("Remove Target Item Pool")).clicked() {
+                                self.plando.item_pool = None;
+                                self.plando.check_item_pool();
+                                ui.close_menu();
+                            }
+                        });
+
                         ui.separator();
                         if ui.button("Reset all Spoiler Overrides").clicked() {
                             self.plando.spoiler_overrides.clear();
+                            self.plando.spoiler_flag_overrides.clear();
                             if self.settings.spoiler_auto_update {
                                 let _ = self.update_spoiler_data_async(self.settings.rebuild_steps);
                             }
@@ -1860,7 +2274,8 @@ impl PlandoApp {
                             self.logic_customization.load(
                                 self.plando.randomizer_settings.clone(),
                                 self.plando.custom_escape_time.clone(),
-                                self.plando.creator_name.clone()
+                                self.plando.creator_name.clone(),
+                                self.plando.custom_objectives.clone()
                             );
                             ui.close_menu();
                         }
@@ -1877,13 +2292,17 @@ impl PlandoApp {
             });
             self.benchmark.split("Draw main menu bar");
 
-            if self.handle_spoiler.is_some() || self.handle_patch.is_some() || self.upload_window.upload_handle.is_some() {
+            if self.handle_spoiler.is_some() || self.handle_fill.is_some() || self.handle_required.is_some() || self.handle_patch.is_some() || self.upload_window.upload_handle.is_some() {
                 egui::Window::new("Updating Async Handle")
                 .resizable(false).movable(false).title_bar(false).min_width(320.0)
                 .fixed_pos(Vec2::new(rt.size().x as f32 - *sidebar_width - 320.0, 32.0).to_pos2()).show(ctx, |ui| {
                     ui.horizontal(|ui| {
                         let str = if self.handle_spoiler.is_some() {
                             "Updating Spoiler Data..."
+                        } else if self.handle_fill.is_some() {
+                            "Filling Item Locations..."
+                        } else if self.handle_required.is_some() {
+                            "Analyzing required Items..."
                         } else if self.handle_patch.is_some() {
                             "Patching ROM..."
                         } else if self.upload_window.upload_handle.is_some() {
@@ -1894,7 +2313,32 @@ impl PlandoApp {
 
                         ui.label(str);
                         ui.spinner();
-                    })
+                    });
+
+                    if self.handle_spoiler.is_some() {
+                        let progress = self.plando.get_spoiler_progress();
+                        let fraction = if progress.placed_items == 0 { 0.0 } else { progress.bireachable_items as f32 / progress.placed_items as f32 };
+                        ui.horizontal(|ui| {
+                            ui.add(egui::ProgressBar::new(fraction).desired_width(240.0).text(
+                                format!("Step {}: {}/{} items, {} flags", progress.step, progress.bireachable_items, progress.placed_items, progress.bireachable_flags)
+                            ));
+                            if ui.button("Cancel").clicked() {
+                                self.plando.cancel_spoiler_update();
+                                self.handle_spoiler = None;
+                            }
+                        });
+                    }
+                    if self.handle_spoiler.is_none() && self.handle_fill.is_none() && self.handle_required.is_some() {
+                        let progress = self.plando.get_required_items_progress();
+                        let fraction = if progress.total == 0 { 0.0 } else { progress.analyzed as f32 / progress.total as f32 };
+                        ui.horizontal(|ui| {
+                            ui.add(egui::ProgressBar::new(fraction).desired_width(240.0).text(format!("{}/{} items", progress.analyzed, progress.total)));
+                            if ui.button("Cancel").clicked() {
+                                self.plando.cancel_required_items_analysis();
+                                self.handle_required = None;
+                            }
+                        });
+                    }
                 });
             }
             self.benchmark.split("Draw async handle progress window");
@@ -1930,6 +2374,16 @@ impl PlandoApp {
                 self.benchmark.split("Draw spoiler overrides");
             }
 
+            if self.seed_report.is_some() {
+                self.draw_seed_report_window(ctx);
+                self.benchmark.split("Draw seed report window");
+            }
+
+            if !self.route_query_nodes.is_empty() {
+                self.draw_route_query_window(ctx);
+                self.benchmark.split("Draw route query window");
+            }
+
             if self.settings_open {
                 self.settings_open = self.draw_settings_window(ctx);
                 self.benchmark.split("Draw settings window");
@@ -1978,6 +2432,8 @@ impl PlandoApp {
                         };
                         self.plando.creator_name = self.logic_customization.creator_name.clone();
                         self.plando.load_preset(self.logic_customization.settings.clone());
+                        self.plando.set_objectives(self.logic_customization.get_custom_objectives());
+                        self.schedule_redraw();
                         // Remove placed starting items
                         for item_idx in 0..self.plando.item_locations.len() {
                             let item = self.plando.item_locations[item_idx];
@@ -2000,6 +2456,34 @@ impl PlandoApp {
                     }
                     Err(err) => self.modal_type = ModalType::Error(err.to_string())
                 }
+                if self.logic_customization.difficulty_analysis_requested {
+                    self.logic_customization.difficulty_analysis_requested = false;
+                    match self.plando.analyze_min_difficulty() {
+                        Ok(handle) => {
+                            self.handle_difficulty = Some(handle);
+                            self.logic_customization.difficulty_analysis_running = true;
+                        }
+                        Err(err) => self.modal_type = ModalType::Error(err.to_string())
+                    }
+                }
+                if self.logic_customization.preset_comparison_requested {
+                    self.logic_customization.preset_comparison_requested = false;
+                    let skill_presets = &self.logic_customization.preset_data.skill_presets;
+                    let [idx_a, idx_b] = self.logic_customization.compare_presets;
+                    match self.plando.compare_presets(&skill_presets[idx_a], &skill_presets[idx_b]) {
+                        Ok(handle) => {
+                            self.handle_compare = Some(handle);
+                            self.preset_comparison_edit_count = self.plando.get_edit_count();
+                            self.logic_customization.preset_comparison_running = true;
+                        }
+                        Err(err) => self.modal_type = ModalType::Error(err.to_string())
+                    }
+                }
+                if self.logic_customization.preset_comparison_clear_requested {
+                    self.logic_customization.preset_comparison_clear_requested = false;
+                    self.preset_comparison = None;
+                    self.schedule_redraw();
+                }
                 self.benchmark.split("Draw logic customization window");
             }
 
@@ -2118,32 +2602,20 @@ impl PlandoApp {
         }
 
         // Update dragged room positions
-        let mut should_redraw = self.map_editor.move_dragged_rooms(&mut self.plando.map_editor, mouse_tile_x, mouse_tile_y, &self.plando.game_data);
+        let mut should_redraw = self.map_editor.move_dragged_rooms(&mut self.plando, mouse_tile_x, mouse_tile_y);
 
         // Start and stop drags
         if self.is_mouse_public && self.mouse_state.is_button_pressed(mouse::Button::Left) {
-            self.map_editor.start_drag(self.plando.map(), last_hovered_room_idx, mouse_tile_x, mouse_tile_y, &self.plando.game_data);
+            self.map_editor.start_drag(&mut self.plando, last_hovered_room_idx, mouse_tile_x, mouse_tile_y);
             self.spoiler_type.set(SpoilerType::None);
         }
 
         if self.mouse_state.is_button_released(mouse::Button::Left) {
             should_redraw = !self.map_editor.dragged_room_idx.is_empty();
 
-            self.map_editor.stop_drag(&mut self.plando.map_editor, mouse_tile_x, mouse_tile_y, &self.plando.game_data, &self.plando.locked_doors);
+            self.map_editor.stop_drag(&mut self.plando, mouse_tile_x, mouse_tile_y);
 
             if should_redraw {
-                // Fix any potential issues that may come up such as syncing up door locks
-                self.plando.update_randomizable_doors();
-
-                let door_locks = self.plando.locked_doors.clone();
-                self.plando.clear_doors();
-                for door in door_locks {
-                    let (room_idx, door_idx) = self.plando.game_data.room_and_door_idxs_by_door_ptr_pair[&door.src_ptr_pair];
-                    if self.plando.place_door(room_idx, door_idx, Some(door.door_type), false).is_err() {
-                        // TODO: Potentially Notify user?
-                    }
-                }
-
                 if self.settings.spoiler_auto_update {
                     if let Err(err) = self.update_spoiler_data_async(self.settings.rebuild_steps) {
                         self.modal_type = ModalType::Error(err.to_string());
@@ -2177,13 +2649,51 @@ impl PlandoApp {
             }
             MapErrorType::AreaNoMap(_) => vec![],
             MapErrorType::AreaSplit(_) => vec![],
-            MapErrorType::_ItemNotReachable(idx) => {
+            MapErrorType::ItemNotReachable(idx) => {
                 let (room_id, node_id) = self.plando.game_data.item_locations[idx];
                 let room_idx = self.plando.room_id_to_idx(room_id);
                 let (tile_x, tile_y) = self.plando.game_data.node_coords[&(room_id, node_id)];
                 let (room_x, room_y) = self.plando.map().rooms[room_idx];
                 vec![IntRect::new((room_x + tile_x) as i32, (room_y + tile_y) as i32, 1, 1)]
             }
+            MapErrorType::ItemMissing(_, _, _) => vec![],
+            MapErrorType::ItemSurplus(placeable, _, _) => {
+                let item = placeable.to_item().unwrap();
+                (0..self.plando.item_locations.len()).filter(|&idx| self.plando.item_locations[idx] == item).map(|idx| {
+                    let (room_id, node_id) = self.plando.game_data.item_locations[idx];
+                    let room_idx = self.plando.room_id_to_idx(room_id);
+                    let (tile_x, tile_y) = self.plando.game_data.node_coords[&(room_id, node_id)];
+                    let (room_x, room_y) = self.plando.map().rooms[room_idx];
+                    IntRect::new((room_x + tile_x) as i32, (room_y + tile_y) as i32, 1, 1)
+                }).collect()
+            }
+            MapErrorType::ProgressionItemZero(_) => vec![],
+            MapErrorType::ItemLocationMissing(_, _, _) => vec![],
+            MapErrorType::OverrideLocationMissing(_, _, _) => vec![],
+            MapErrorType::OverrideFlagMissing(_) => vec![],
+            MapErrorType::StartLocationMissing(_, _) => vec![],
+            MapErrorType::DoorLockMissing(_) => vec![],
+            MapErrorType::TechMissing(_) => vec![],
+            MapErrorType::NotableMissing(_, _) => vec![],
+            MapErrorType::GameDataChanged => vec![],
+            MapErrorType::OverridePastLastStep(target, _, _) | MapErrorType::OverrideInLogic(target, _, _)
+            | MapErrorType::OverrideDuplicate(target, _) | MapErrorType::OverrideNoDescription(target, _) => match target {
+                OverrideTarget::Item(idx) => {
+                    let (room_id, node_id) = self.plando.game_data.item_locations[idx];
+                    let room_idx = self.plando.room_id_to_idx(room_id);
+                    let (tile_x, tile_y) = self.plando.game_data.node_coords[&(room_id, node_id)];
+                    let (room_x, room_y) = self.plando.map().rooms[room_idx];
+                    vec![IntRect::new((room_x + tile_x) as i32, (room_y + tile_y) as i32, 1, 1)]
+                }
+                OverrideTarget::Flag(_) => vec![]
+            }
+            MapErrorType::ItemSoftLock(idx, _, _) => {
+                let (room_id, _) = self.plando.game_data.item_locations[idx];
+                vec![to_sf_rect(self.plando.map_editor.get_room_bounds(self.plando.room_id_to_idx(room_id)))]
+            }
+            MapErrorType::RoomSoftLock(room_idx, _, _) => {
+                vec![to_sf_rect(self.plando.map_editor.get_room_bounds(room_idx))]
+            }
             MapErrorType::AreaBounds(area, _, _) => {
                 (0..self.plando.map().rooms.len()).filter(|&room_idx| {
                     self.plando.map().area[room_idx] == area && self.plando.map().room_mask[room_idx]
@@ -2461,7 +2971,10 @@ impl PlandoApp {
 
     fn draw_items(&mut self, rt: &mut dyn RenderTarget, states: &RenderStates, sidebar_selection: Option<Placeable>) {
         let mut info_overlay = None;
+        let mut comparison_overlay = None;
+        let mut requirement_overlay = None;
         let mut update_spoiler = false;
+        let mut route_node = None;
         if sidebar_selection.is_none() || sidebar_selection.is_some_and(|x| x >= Placeable::ETank) {
             for i in 0..self.plando.item_locations.len() {
                 let item = self.plando.item_locations[i];
@@ -2493,6 +3006,38 @@ impl PlandoApp {
                 };
                 spr_item.set_position(Vector2f::new((8 * (tile_x + room_x) + item_x_offset) as f32, (8 * (tile_y + room_y) + 4) as f32));
                 spr_item.set_scale(6.0 / tex_item_width as f32);
+
+                // Red: Only reachable with one of the compared presets, Yellow: Different step, Magenta: New tech with the second preset
+                if let Some(diff) = self.preset_comparison.as_ref().and_then(|x| x.get_item_diff(i)) {
+                    let color = if diff.is_unreachable() {
+                        Color::RED
+                    } else if diff.is_step_changed() {
+                        Color::YELLOW
+                    } else {
+                        Color::MAGENTA
+                    };
+                    let mut highlight = graphics::RectangleShape::with_size(Vector2f::new(7.0, 7.0));
+                    highlight.set_origin(Vector2f::new(3.5, 3.5));
+                    highlight.set_position(spr_item.position());
+                    highlight.set_fill_color(Color::TRANSPARENT);
+                    highlight.set_outline_color(color);
+                    highlight.set_outline_thickness(0.5);
+                    rt.draw_with_renderstates(&highlight, &states);
+                }
+
+                // Hidden once a different item is placed
+                let required_item = self.required_items.as_ref().and_then(|x| x.get_item(i)).filter(|x| x.item == item);
+                let required_icon = required_item.map(|result| {
+                    let color = get_requirement_color(result.requirement);
+                    let mut icon = graphics::CircleShape::new(1.0, 12);
+                    icon.set_origin(Vector2f::new(1.0, 1.0));
+                    icon.set_position(spr_item.position() + Vector2f::new(2.5, -2.5));
+                    icon.set_fill_color(color);
+                    icon.set_outline_color(Color::BLACK);
+                    icon.set_outline_thickness(0.25);
+                    icon
+                });
+
                 if spr_item.global_bounds().contains2(self.local_mouse_x, self.local_mouse_y) && self.is_mouse_public {
                     spr_item.scale(1.2);
                     let item_name = if item == Item::Nothing {
@@ -2501,9 +3046,20 @@ impl PlandoApp {
                         &self.plando.game_data.item_isv.keys[item as usize]
                     };
                     info_overlay = Some(item_name.clone());
+                    if let Some(result) = required_item {
+                        requirement_overlay = Some((result.requirement.to_string(), get_requirement_color(result.requirement)));
+                    }
+                    if let Some(comparison) = &self.preset_comparison {
+                        if let Some(diff) = comparison.get_item_diff(i) {
+                            let step_str = |step: Option<usize>| step.map(|x| format!("Step {x}")).unwrap_or("Unreachable".to_string());
+                            comparison_overlay = Some(format!("{}: {}, {}: {}", comparison.name_a, step_str(diff.step_a), comparison.name_b, step_str(diff.step_b)));
+                        }
+                    }
 
                     if self.mouse_state.consume_click(mouse::Button::Left) {
-                        if sidebar_selection.is_some_and(|x| x.to_item().is_some()) {
+                        if self.key_state.is_key_down(Key::LShift) {
+                            route_node = Some((room_id, node_id));
+                        } else if sidebar_selection.is_some_and(|x| x.to_item().is_some()) {
                             let item_to_place = sidebar_selection.unwrap().to_item().unwrap();
                             let as_placeable = sidebar_selection.unwrap();
                             let max_count = self.plando.get_max_placeable_count(as_placeable).unwrap_or(999);
@@ -2527,6 +3083,9 @@ impl PlandoApp {
                 }
 
                 rt.draw_with_renderstates(&spr_item, &states);
+                if let Some(icon) = required_icon {
+                    rt.draw_with_renderstates(&icon, &states);
+                }
             }
         }
 
@@ -2535,14 +3094,24 @@ impl PlandoApp {
                 self.modal_type = ModalType::Error(err.to_string());
             }
         }
+        if let Some(node) = route_node {
+            self.select_route_node(node);
+        }
 
         if let Some(io) = info_overlay {
             self.layout.info_overlay_builder.new_line(io, Color::WHITE);
         }
+        if let Some(io) = comparison_overlay {
+            self.layout.info_overlay_builder.new_line(io, Color::YELLOW);
+        }
+        if let Some((io, color)) = requirement_overlay {
+            self.layout.info_overlay_builder.new_line(io, color);
+        }
     }
 
     fn draw_flags(&mut self, rt: &mut dyn RenderTarget, states: &RenderStates, sidebar_selection: Option<Placeable>) {
         let mut info_overlay = None;
+        let mut route_node = None;
         for (i, &flag_id) in self.plando.game_data.flag_ids.iter().enumerate() {
             let vertex_info = &get_vertex_info(self.plando.game_data.flag_vertex_ids[i][0], &self.plando.game_data, self.plando.map());
             let room_idx = self.plando.room_id_to_idx(vertex_info.room_id);
@@ -2573,7 +3142,11 @@ impl PlandoApp {
                 info_overlay = Some(flag_str.to_string());
 
                 if sidebar_selection.is_none() && self.mouse_state.consume_click(mouse::Button::Left) {
-                    self.spoiler_type.set(SpoilerType::Flag(i));
+                    if self.key_state.is_key_down(Key::LShift) {
+                        route_node = Some((vertex_info.room_id, vertex_info.node_id));
+                    } else {
+                        self.spoiler_type.set(SpoilerType::Flag(i));
+                    }
                 }
 
                 self.is_mouse_public = false;
@@ -2581,6 +3154,9 @@ impl PlandoApp {
 
             rt.draw_with_renderstates(&spr_flag, &states);
         }
+        if let Some(node) = route_node {
+            self.select_route_node(node);
+        }
 
         if let Some(io) = info_overlay {
             self.layout.info_overlay_builder.new_line(io, Color::WHITE);
@@ -2651,6 +3227,15 @@ impl PlandoApp {
     }
 
     fn draw_spoiler_route(&mut self, rt: &mut dyn RenderTarget, states: &RenderStates) {
+        // A reachability query replaces the route of the spoiler details until it is closed
+        if let Some(result) = &self.route_query {
+            let vertex_route: Vec<_> = result.route.iter().filter_map(|entry| entry.coords).map(|(x, y)| {
+                graphics::Vertex::with_pos_color(Vector2f::new(x as f32 + 0.5, y as f32 + 0.5) * 8.0, Color::GREEN)
+            }).collect();
+            draw_thick_line_strip(rt, &states, &vertex_route, 1.0);
+            return;
+        }
+
         if self.spoiler_type.get() == SpoilerType::None {
             return;
         }
@@ -2781,6 +3366,7 @@ impl PlandoApp {
                         SidebarPanel::Rooms => "Rooms".to_string(),
                         SidebarPanel::Areas => "Areas".to_string(),
                         SidebarPanel::Errors => format!("Errors ({})", self.plando.map_editor.error_list.len()),
+                        SidebarPanel::Tech => "Tech".to_string(),
                         SidebarPanel::Benchmark => "Benchmark".to_string()
                     };
 
@@ -2806,6 +3392,7 @@ impl PlandoApp {
             SidebarPanel::Rooms => self.draw_sidebar_room_select(ui),
             SidebarPanel::Areas => self.draw_sidebar_area_select(ui),
             SidebarPanel::Errors => self.draw_sidebar_error_list(ui),
+            SidebarPanel::Tech => self.draw_sidebar_tech_report(ui),
             SidebarPanel::Benchmark => self.draw_sidebar_benchmark(ui, last_benchmark),
         }
     }
@@ -2840,6 +3427,25 @@ impl PlandoApp {
                     if ui.add(label_count).clicked() {
                         *sidebar_selection = Some(Placeable::VARIANTS[row]);
                     }
+                    // Target count of the item pool. Yellow if too few are placed, red if too many
+                    if let Some(mut target_count) = self.plando.get_target_item_count(*placeable) {
+                        let col = if item_count < target_count {
+                            Color32::YELLOW
+                        } else if item_count > target_count {
+                            Color32::RED
+                        } else {
+                            ui.style().visuals.text_color()
+                        };
+                        let drag_target = egui::DragValue::new(&mut target_count).prefix("Target: ").speed(0.1).range(0..=100);
+                        let resp = ui.scope(|ui| {
+                            ui.visuals_mut().override_text_color = Some(col);
+                            ui.add(drag_target)
+                        }).inner;
+                        if resp.changed() {
+                            self.plando.set_target_item_count(*placeable, target_count);
+                            self.plando.check_item_pool();
+                        }
+                    }
                     // So it doesn't create an empty row at the very end
                     if row + 1 < Placeable::VARIANTS.len() {
                         ui.end_row();
@@ -2866,7 +3472,7 @@ impl PlandoApp {
                 }
                 if ui.add(btn).clicked() {
                     if is_missing {
-                        self.plando.map_editor.spawn_room(room_idx, &self.plando.locked_doors);
+                        self.plando.spawn_room(room_idx);
                     }
                     let (room_x, room_y) = self.plando.map().rooms[room_idx];
                     let room_width = room_geometry.map[0].len() as f32 * 8.0;
@@ -2902,12 +3508,14 @@ impl PlandoApp {
 
                 let btn = egui::Button::new(RichText::new(area_str).color(stroke_col.clone())).fill(col32).min_size(Vec2 { x: 256.0, y: 1.0 });
                 if ui.add(btn).clicked() && !self.map_editor.selected_room_idx.is_empty() {
+                    self.plando.begin_command(Command::ApplyArea);
                     for i in 0..self.map_editor.selected_room_idx.len() {
                         let room_idx = self.map_editor.selected_room_idx[i];
                         let sub_area = self.plando.map().subarea[room_idx];
                         let sub_sub_area = self.plando.map().subsubarea[room_idx];
-                        self.plando.map_editor.apply_area(room_idx, map_editor::Area::from_tuple((idx, sub_area, sub_sub_area)), &self.plando.locked_doors);
+                        self.plando.apply_area(room_idx, map_editor::Area::from_tuple((idx, sub_area, sub_sub_area)));
                     }
+                    self.plando.end_command();
                     self.schedule_redraw();
                 }
             }
@@ -2921,9 +3529,11 @@ impl PlandoApp {
 
                 let btn = egui::Button::new(RichText::new(area_value.to_string()).color(stroke_col.clone())).fill(col32).min_size(Vec2 { x: 256.0, y: 1.0 });
                 if ui.add(btn).clicked() && !self.map_editor.selected_room_idx.is_empty() {
+                    self.plando.begin_command(Command::ApplyArea);
                     for i in 0..self.map_editor.selected_room_idx.len() {
-                        self.plando.map_editor.apply_area(self.map_editor.selected_room_idx[i], area_value, &self.plando.locked_doors);
+                        self.plando.apply_area(self.map_editor.selected_room_idx[i], area_value);
                     }
+                    self.plando.end_command();
                     self.schedule_redraw();
                 }
             }
@@ -2936,7 +3546,7 @@ impl PlandoApp {
                 }
             });
             if ui.button("Swap!").clicked() {
-                self.plando.map_editor.swap_areas(self.map_editor.swap_first, self.map_editor.swap_second, &self.plando.locked_doors);
+                self.plando.swap_areas(self.map_editor.swap_first, self.map_editor.swap_second);
                 self.schedule_redraw();
             }
             egui::ComboBox::from_id_salt("combo_swap_area_second").selected_text(areas[self.map_editor.swap_second]).show_ui(ui, |ui| {
@@ -2964,6 +3574,9 @@ impl PlandoApp {
                     if error.is_severe() { Color32::from_rgb(110, 24, 24) } else { Color32::from_rgb(84, 80, 0) }
                 ).min_size(Vec2 { x: w, y: 1.0 });
                 if ui.add(bt).clicked() {
+                    if let Some(step) = error.get_step() {
+                        self.spoiler_step = step.saturating_sub(1);
+                    }
                     let rects = self.get_error_rects(error.clone());
                     if !rects.is_empty() {
                         let bbox = rects.into_iter().reduce(|acc, e| {
@@ -2987,6 +3600,38 @@ impl PlandoApp {
         });
     }
 
+    fn draw_sidebar_tech_report(&mut self, ui: &mut Ui) {
+        let Some(report) = &self.tech_report else {
+            ui.label("Update the spoiler log to see which tech and notable strats the seed requires");
+            return;
+        };
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let lists = [
+                ("Tech required", &report.tech),
+                ("Notable Strats required", &report.notables),
+                ("Tech possibly used", &report.possible_tech),
+                ("Notable Strats possibly used", &report.possible_notables)
+            ];
+            for (heading, usages) in lists {
+                ui.heading(format!("{heading} ({})", usages.len()));
+                for (idx, usage) in usages.iter().enumerate() {
+                    let title = format!("{} [{}] ({} uses)", usage.name, usage.difficulty, usage.uses.len());
+                    egui::CollapsingHeader::new(title).id_salt(format!("tech_report_{heading}_{idx}")).show(ui, |ui| {
+                        for strat_usage in &usage.uses {
+                            let step_str = match strat_usage.step {
+                                Some(step) => format!("Step {step}"),
+                                None => "Hub".to_string()
+                            };
+                            ui.label(format!("{step_str}: {} ({}: {})", strat_usage.target, strat_usage.room, strat_usage.strat));
+                        }
+                    });
+                }
+                ui.separator();
+            }
+        });
+    }
+
     fn draw_sidebar_benchmark(&mut self, ui: &mut Ui, benchmark: &BenchmarkResult) {
         ui.heading("Benchmark results of last frame");
         ui.separator();
@@ -3018,6 +3663,39 @@ impl PlandoApp {
             ui.label(format!("{} ms", benchmark.total_time.as_millis()));
             ui.label("100%");
         });
+
+        let timings = self.plando.get_logic_timings();
+        let Some(last) = timings.last else {
+            return;
+        };
+        ui.separator();
+        ui.heading("Last spoiler update");
+        egui::Grid::new("grid_benchmark_logic").num_columns(3).show(ui, |ui| {
+            ui.label("");
+            ui.label("Last");
+            ui.label("Last full");
+            ui.end_row();
+
+            let full = timings.last_full;
+            let rows: [(&str, fn(&LogicTiming) -> std::time::Duration); 5] = [
+                ("Links", |x| x.links),
+                ("Setup", |x| x.setup),
+                ("Traversal", |x| x.traversal),
+                ("Randomization", |x| x.randomization),
+                ("Total time", |x| x.total)
+            ];
+            for (label, get_dur) in rows {
+                ui.label(label);
+                ui.label(format!("{} ms", get_dur(&last).as_millis()));
+                ui.label(full.as_ref().map(|x| format!("{} ms", get_dur(x).as_millis())).unwrap_or("-".to_string()));
+                ui.end_row();
+            }
+        });
+        let links_str = if last.links_cached { "Links reused" } else { "Links filtered" };
+        match last.resumed_step {
+            Some(step) => ui.label(format!("{links_str}, resumed from step {step}")),
+            None => ui.label(format!("{links_str}, full traversal"))
+        };
     }
 
     fn draw_spoiler_override(&mut self, ctx: &Context) -> bool {
@@ -3074,12 +3752,36 @@ impl PlandoApp {
                 ui.separator();
             }
 
+            let mut remove_flag_idx = None;
+            let flag_overrides: Vec<_> = self.plando.spoiler_flag_overrides.iter_mut().enumerate().filter(|(_idx, x)| x.step == step).collect();
+            for (override_idx, flag_override) in flag_overrides {
+                ui.horizontal(|ui| {
+                    let cur_flag_name = &self.plando.game_data.flag_isv.keys[flag_override.flag_id];
+                    hovered |= egui::ComboBox::new(format!("combo_spoiler_override_flag_{override_idx}"), "Flag").selected_text(cur_flag_name).show_ui(ui, |ui| {
+                        for &flag_id in &self.plando.game_data.flag_ids {
+                            ui.selectable_value(&mut flag_override.flag_id, flag_id, &self.plando.game_data.flag_isv.keys[flag_id]);
+                        }
+                    }).response.contains_pointer();
+
+                    if ui.button("Delete").clicked() {
+                        remove_flag_idx = Some(override_idx);
+                    }
+                });
+
+                ui.label("Description");
+                ui.text_edit_multiline(&mut flag_override.description);
+                ui.separator();
+            }
+
             if let Some(idx) = remove_idx {
                 self.plando.spoiler_overrides.remove(idx);
             }
+            if let Some(idx) = remove_flag_idx {
+                self.plando.spoiler_flag_overrides.remove(idx);
+            }
 
             ui.horizontal(|ui| {
-                if ui.button("New").clicked() {
+                if ui.button("New Item").clicked() {
                     match self.plando.item_locations.iter().position(|&item| item != Item::Nothing) {
                         None => self.modal_type = ModalType::Error("There needs to be at least one item placed to configure overrides".to_string()),
                         Some(item_idx) => self.plando.spoiler_overrides.push(SpoilerOverride {
@@ -3089,6 +3791,13 @@ impl PlandoApp {
                         })
                     }
                 }
+                if ui.button("New Flag").clicked() {
+                    self.plando.spoiler_flag_overrides.push(SpoilerFlagOverride {
+                        step,
+                        flag_id: self.plando.game_data.mother_brain_defeated_flag_id,
+                        description: String::new()
+                    });
+                }
                 if ui.button("Apply").clicked() {
                     self.override_window = None;
                     if self.settings.spoiler_auto_update {
@@ -3290,6 +3999,14 @@ impl PlandoApp {
                             ui.separator();
                         }
                     }
+                    if let SpoilerType::Flag(flag_idx) = self.spoiler_type.get() {
+                        let flag_id = self.plando.game_data.flag_ids[flag_idx];
+                        if let Some(flag_override) = self.plando.spoiler_flag_overrides.iter().find(|x| x.flag_id == flag_id) {
+                            ui.label("OVERRIDE DESCRIPTION");
+                            ui.label(&flag_override.description);
+                            ui.separator();
+                        }
+                    }
 
                     let reset = self.spoiler_type.reset();
 
@@ -3497,6 +4214,48 @@ impl PlandoApp {
         spoiler_window_bounds
     }
 
+    fn draw_seed_report_window(&mut self, ctx: &Context) {
+        let Some((title, lines)) = &self.seed_report else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new(title.as_str()).open(&mut open).resizable(true).show(ctx, |ui| {
+            egui::ScrollArea::vertical().max_height(480.0).show(ui, |ui| {
+                for line in lines {
+                    ui.label(line);
+                }
+            });
+        });
+        if !open {
+            self.seed_report = None;
+        }
+    }
+
+    fn draw_route_query_window(&mut self, ctx: &Context) {
+        let node_name = |&(room_id, node_id): &(usize, usize)| {
+            let info = get_vertex_info_by_id(room_id, node_id, &self.plando.game_data, self.plando.map());
+            format!("{}: {}", info.room_name, info.node_name)
+        };
+        let mut open = true;
+        egui::Window::new("Route Query").open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.label(format!("From: {}", node_name(&self.route_query_nodes[0])));
+            match self.route_query_nodes.get(1) {
+                Some(target) => ui.label(format!("To: {}", node_name(target))),
+                None => ui.label("Shift-click a second item or flag as the target")
+            };
+            ui.separator();
+            match &self.route_query {
+                Some(result) if result.reachable => ui.label(format!("Reachable, the route is drawn in green ({} links)", result.route.len())),
+                Some(_) => ui.label("Not reachable with the items and flags collected before the current spoiler step"),
+                None if self.handle_route.is_some() => ui.spinner(),
+                None => ui.label("")
+            };
+        });
+        if !open {
+            self.clear_route_query();
+        }
+    }
+
     fn draw_settings_window(&mut self, ctx: &Context) -> bool {
         let mut settings_open = true;
         let settings_path_str = PlandoApp::SETTINGS_PATH;